@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS quote_revisions (
+    quote_id UUID NOT NULL REFERENCES quotes (id) ON DELETE CASCADE,
+    version INT NOT NULL,
+    author TEXT NOT NULL,
+    quote TEXT NOT NULL,
+    revised_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (quote_id, version)
+);
+
+-- seed the history with the current version of any existing quotes
+INSERT INTO quote_revisions (quote_id, version, author, quote, revised_at)
+SELECT id, version, author, quote, created_at FROM quotes
+ON CONFLICT DO NOTHING;
\ No newline at end of file
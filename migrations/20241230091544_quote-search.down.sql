@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS quotes_search_vector_idx;
+
+ALTER TABLE quotes DROP COLUMN IF EXISTS search_vector;
\ No newline at end of file
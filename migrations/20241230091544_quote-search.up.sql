@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE quotes ADD COLUMN IF NOT EXISTS search_vector TSVECTOR
+    GENERATED ALWAYS AS (to_tsvector('english', author || ' ' || quote)) STORED;
+
+CREATE INDEX IF NOT EXISTS quotes_search_vector_idx ON quotes USING GIN (search_vector);
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS quote_tags;
+
+DROP TABLE IF EXISTS tags;
\ No newline at end of file
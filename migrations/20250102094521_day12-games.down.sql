@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS game_moves;
+
+DROP TABLE IF EXISTS games;
\ No newline at end of file
@@ -0,0 +1,19 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS games (
+    id UUID PRIMARY KEY,
+    width INT NOT NULL,
+    height INT NOT NULL,
+    connect INT NOT NULL,
+    strict BOOLEAN NOT NULL DEFAULT FALSE,
+    cookie_token TEXT,
+    milk_token TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS game_moves (
+    game_id UUID NOT NULL REFERENCES games (id) ON DELETE CASCADE,
+    number INT NOT NULL,
+    team TEXT NOT NULL CHECK (team IN ('cookie', 'milk')),
+    column_index INT NOT NULL,
+    PRIMARY KEY (game_id, number)
+);
\ No newline at end of file
@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE games ADD COLUMN IF NOT EXISTS tiles TEXT[];
\ No newline at end of file
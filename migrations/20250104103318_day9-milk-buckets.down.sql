@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS milk_buckets;
+
+DROP TABLE IF EXISTS milk_bucket_config;
\ No newline at end of file
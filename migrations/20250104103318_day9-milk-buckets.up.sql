@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS milk_bucket_config (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    capacity INT NOT NULL CHECK (capacity > 0),
+    refill_interval_ms BIGINT NOT NULL CHECK (refill_interval_ms > 0)
+);
+
+CREATE TABLE IF NOT EXISTS milk_buckets (
+    client TEXT PRIMARY KEY,
+    tokens DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp()
+);
+
+CREATE INDEX IF NOT EXISTS milk_buckets_updated_at_idx ON milk_buckets (updated_at);
\ No newline at end of file
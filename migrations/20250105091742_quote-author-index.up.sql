@@ -0,0 +1,2 @@
+-- Add up migration script here
+CREATE INDEX IF NOT EXISTS quotes_author_id_idx ON quotes (lower(author), id) WHERE deleted_at IS NULL;
\ No newline at end of file
@@ -1,14 +1,22 @@
 // src/main.rs
 
 // dependencies
+use shuttle_runtime::SecretStore;
 use shuttle_shared_db::Postgres;
+use shuttlings_cch24::bucket_repository::{BucketBackend, PostgresBucketRepository};
+use shuttlings_cch24::rate_limit::BucketConfig;
 use shuttlings_cch24::startup::{AppState, Application};
 use shuttlings_cch24::telemetry::{get_subscriber, init_subscriber};
 use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
 
 // main function
 #[shuttle_runtime::main]
-async fn main(#[Postgres] pool: PgPool) -> shuttle_axum::ShuttleAxum {
+async fn main(
+    #[Postgres] pool: PgPool,
+    #[shuttle_runtime::Secrets] secrets: SecretStore,
+) -> shuttle_axum::ShuttleAxum {
     // initialize tracing
     let subscriber = get_subscriber("shuttlings-cch24".into(), "info".into(), std::io::stdout);
     init_subscriber(subscriber);
@@ -24,7 +32,57 @@ async fn main(#[Postgres] pool: PgPool) -> shuttle_axum::ShuttleAxum {
     tracing::info!("Initializing the application state...");
     let milk_bucket_max_volume = 5; // max milk bucket volume, in gallons
     let milk_refill_rate = 1; // milk bucket refill rate, in gallons
-    let app_state = AppState::new(milk_bucket_max_volume, milk_refill_rate, pool);
+    let admin_token = secrets.get("ADMIN_TOKEN"); // bearer token for the admin endpoints, such as /9/config
+    if admin_token.is_none() {
+        tracing::warn!("No ADMIN_TOKEN secret is set, so the admin endpoints are disabled.");
+    }
+    let api_keys: Vec<String> = secrets
+        .get("API_KEYS") // comma separated keys which give a client its own milk bucket
+        .map(|keys| {
+            keys.split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    let milk_bucket_backend: BucketBackend = secrets
+        .get("MILK_BUCKET_BACKEND") // "memory" (the default), or "postgres" to share the milk buckets
+        .map(|backend| {
+            backend
+                .parse()
+                .expect("Unknown MILK_BUCKET_BACKEND secret.")
+        })
+        .unwrap_or_default();
+    let app_state = AppState::new(milk_bucket_max_volume, milk_refill_rate, pool.clone())
+        .with_admin_token(admin_token)
+        .with_api_keys(api_keys);
+    let app_state = match milk_bucket_backend {
+        BucketBackend::Memory => app_state,
+        BucketBackend::Postgres => {
+            let milk_bucket_config = BucketConfig::new(
+                milk_bucket_max_volume,
+                Duration::from_secs(milk_refill_rate),
+            )
+            .expect("Invalid milk bucket settings.");
+            app_state.with_milk_buckets(Arc::new(PostgresBucketRepository::new(
+                pool,
+                milk_bucket_config,
+            )))
+        }
+    };
+    tracing::info!(
+        "Keeping the Day 9 milk buckets in {:?}.",
+        milk_bucket_backend
+    );
+
+    // reload the Day 12 games saved before the last restart
+    let restored_games = app_state
+        .restore_games()
+        .await
+        .expect("Unable to restore the Day 12 games.");
+    tracing::info!("Restored {} Day 12 games.", restored_games);
+    app_state.spawn_game_pruning();
 
     // build the application
     tracing::info!("Building the application...");
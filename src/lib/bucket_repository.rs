@@ -0,0 +1,324 @@
+// src/lib/bucket_repository.rs
+
+// dependencies
+use crate::errors::AppError;
+use crate::rate_limit::{BucketConfig, ClientBuckets, RateLimitOutcome};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+// how often the Postgres milk buckets which have refilled completely are deleted, at most
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+// trait to represent the storage behind the Day 9 milk buckets; kept in memory, each instance of the app
+// has its own buckets, while kept in Postgres, the buckets and their configuration are shared by every
+// instance using the same database
+#[async_trait]
+pub trait BucketRepository: Debug + Send + Sync {
+    // the size of every bucket, and how quickly it refills
+    async fn config(&self) -> Result<BucketConfig, AppError>;
+
+    // change the size and refill rate of every bucket; each client keeps the tokens it had, up to the
+    // new capacity
+    async fn configure(&self, config: BucketConfig) -> Result<(), AppError>;
+
+    // the tokens in the client's bucket, which is full for a client not seen recently
+    async fn level(&self, client: &str) -> Result<f64, AppError>;
+
+    // take a token from the client's bucket
+    async fn try_acquire(&self, client: &str) -> Result<RateLimitOutcome, AppError>;
+
+    // fill the client's bucket back up
+    async fn refill(&self, client: &str) -> Result<(), AppError>;
+}
+
+// enum type to represent where the milk buckets are kept, chosen by the MILK_BUCKET_BACKEND secret
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BucketBackend {
+    #[default]
+    Memory,
+    Postgres,
+}
+
+// implement the FromStr trait for the BucketBackend type
+impl FromStr for BucketBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "memory" => Ok(Self::Memory),
+            "postgres" => Ok(Self::Postgres),
+            _ => Err(format!("Unknown milk bucket backend: {}", s)),
+        }
+    }
+}
+
+// struct type to represent the in-memory implementation of the bucket repository, the default
+#[derive(Debug)]
+pub struct InMemoryBucketRepository {
+    buckets: RwLock<ClientBuckets>,
+}
+
+// methods for the InMemoryBucketRepository type
+impl InMemoryBucketRepository {
+    pub fn new(max: usize, interval: Duration) -> Self {
+        Self {
+            buckets: RwLock::new(ClientBuckets::new(max, interval)),
+        }
+    }
+}
+
+// implement the BucketRepository trait for the InMemoryBucketRepository type
+#[async_trait]
+impl BucketRepository for InMemoryBucketRepository {
+    async fn config(&self) -> Result<BucketConfig, AppError> {
+        Ok(self.buckets.read().await.config())
+    }
+
+    async fn configure(&self, config: BucketConfig) -> Result<(), AppError> {
+        self.buckets.write().await.configure(config);
+        Ok(())
+    }
+
+    async fn level(&self, client: &str) -> Result<f64, AppError> {
+        Ok(self.buckets.read().await.level(client))
+    }
+
+    async fn try_acquire(&self, client: &str) -> Result<RateLimitOutcome, AppError> {
+        Ok(self.buckets.write().await.try_acquire(client))
+    }
+
+    async fn refill(&self, client: &str) -> Result<(), AppError> {
+        self.buckets.write().await.refill(client);
+        Ok(())
+    }
+}
+
+// struct type to represent the Postgres implementation of the bucket repository; the configuration set
+// through /9/config is stored too, and until there is one, the configuration given here is used
+#[derive(Clone, Debug)]
+pub struct PostgresBucketRepository {
+    pool: PgPool,
+    initial_config: BucketConfig,
+    last_cleanup: Arc<Mutex<Instant>>,
+}
+
+// struct type to represent the row of the milk_bucket_config table
+#[derive(Debug, sqlx::FromRow)]
+struct ConfigRow {
+    capacity: i32,
+    refill_interval_ms: i64,
+}
+
+// struct type to represent a row of the milk_buckets table, along with the time it was read at, by the
+// database clock every instance shares
+#[derive(Debug, sqlx::FromRow)]
+struct BucketRow {
+    tokens: f64,
+    updated_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+}
+
+// methods for the PostgresBucketRepository type
+impl PostgresBucketRepository {
+    pub fn new(pool: PgPool, initial_config: BucketConfig) -> Self {
+        Self {
+            pool,
+            initial_config,
+            last_cleanup: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    // whether the buckets are due to be cleaned up, claiming the cleanup for the caller if so
+    fn claim_cleanup(&self) -> bool {
+        let mut last_cleanup = self
+            .last_cleanup
+            .lock()
+            .expect("The milk bucket cleanup lock was poisoned.");
+        if last_cleanup.elapsed() < CLEANUP_INTERVAL {
+            return false;
+        }
+        *last_cleanup = Instant::now();
+        true
+    }
+
+    // the stored configuration, or the initial one if none has been stored yet
+    fn config_or_initial(&self, row: Option<ConfigRow>) -> BucketConfig {
+        match row {
+            Some(row) => BucketConfig {
+                capacity: row.capacity as usize,
+                refill_interval: Duration::from_millis(row.refill_interval_ms as u64),
+            },
+            None => self.initial_config,
+        }
+    }
+}
+
+// methods for the BucketRow type
+impl BucketRow {
+    // the tokens in the bucket when it was read
+    fn level(&self, config: &BucketConfig) -> f64 {
+        let elapsed = (self.now - self.updated_at).to_std().unwrap_or_default();
+        config.refilled(self.tokens, elapsed)
+    }
+}
+
+// implement the BucketRepository trait for the PostgresBucketRepository type
+#[async_trait]
+impl BucketRepository for PostgresBucketRepository {
+    async fn config(&self) -> Result<BucketConfig, AppError> {
+        let row: Option<ConfigRow> =
+            sqlx::query_as("SELECT capacity, refill_interval_ms FROM milk_bucket_config")
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(self.config_or_initial(row))
+    }
+
+    async fn configure(&self, config: BucketConfig) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        // hold back every other change to the configuration, and every request, until the new one is in
+        let row: Option<ConfigRow> = sqlx::query_as(
+            "SELECT capacity, refill_interval_ms FROM milk_bucket_config FOR UPDATE",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let old_config = self.config_or_initial(row);
+
+        sqlx::query("UPDATE milk_buckets SET tokens = LEAST(tokens + EXTRACT(EPOCH FROM clock_timestamp() - updated_at)::DOUBLE PRECISION / $1, $2, $3), updated_at = clock_timestamp()")
+            .bind(old_config.refill_interval.as_secs_f64())
+            .bind(old_config.capacity as f64)
+            .bind(config.capacity as f64)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("INSERT INTO milk_bucket_config (id, capacity, refill_interval_ms) VALUES (TRUE, $1, $2) ON CONFLICT (id) DO UPDATE SET (capacity, refill_interval_ms) = (EXCLUDED.capacity, EXCLUDED.refill_interval_ms)")
+            .bind(config.capacity as i32)
+            .bind(config.refill_interval.as_millis() as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn level(&self, client: &str) -> Result<f64, AppError> {
+        let config = self.config().await?;
+        let bucket: Option<BucketRow> = sqlx::query_as(
+            "SELECT tokens, updated_at, clock_timestamp() AS now FROM milk_buckets WHERE client = $1",
+        )
+        .bind(client)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(match bucket {
+            Some(bucket) => bucket.level(&config),
+            None => config.capacity as f64,
+        })
+    }
+
+    async fn try_acquire(&self, client: &str) -> Result<RateLimitOutcome, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let row: Option<ConfigRow> =
+            sqlx::query_as("SELECT capacity, refill_interval_ms FROM milk_bucket_config FOR SHARE")
+                .fetch_optional(&mut *tx)
+                .await?;
+        let config = self.config_or_initial(row);
+
+        // create a full bucket for a client not seen recently, and lock the client's bucket until the
+        // token is taken, so requests to other instances wait their turn; the time is read once the
+        // lock is held
+        let bucket: BucketRow = sqlx::query_as("INSERT INTO milk_buckets (client, tokens) VALUES ($1, $2) ON CONFLICT (client) DO UPDATE SET client = EXCLUDED.client RETURNING tokens, updated_at, clock_timestamp() AS now")
+            .bind(client)
+            .bind(config.capacity as f64)
+            .fetch_one(&mut *tx)
+            .await?;
+        let (tokens, outcome) = config.take(bucket.level(&config));
+
+        sqlx::query("UPDATE milk_buckets SET tokens = $2, updated_at = $3 WHERE client = $1")
+            .bind(client)
+            .bind(tokens)
+            .bind(bucket.now)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        // now and then, forget every bucket which has refilled completely, since a new bucket starts out
+        // full anyway; the request has its token either way, so a failed cleanup is only logged
+        if self.claim_cleanup() {
+            let cleanup = sqlx::query(
+                "DELETE FROM milk_buckets WHERE updated_at < clock_timestamp() - make_interval(secs => $1)",
+            )
+            .bind(config.idle_timeout().as_secs_f64())
+            .execute(&self.pool)
+            .await;
+            if let Err(e) = cleanup {
+                tracing::warn!(error = %e, "Unable to clean up the milk buckets.");
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    async fn refill(&self, client: &str) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM milk_buckets WHERE client = $1")
+            .bind(client)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn bucket_backend_defaults_to_memory_and_parses_in_any_case() {
+        assert_eq!(BucketBackend::default(), BucketBackend::Memory);
+        assert_eq!("Postgres".parse(), Ok(BucketBackend::Postgres));
+        assert!("redis".parse::<BucketBackend>().is_err());
+    }
+
+    #[tokio::test]
+    async fn postgres_cleanup_is_claimed_at_most_once_per_interval() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let config = BucketConfig::new(5, Duration::from_secs(1)).unwrap();
+        let repository = PostgresBucketRepository::new(pool, config);
+        assert!(!repository.claim_cleanup());
+
+        *repository.last_cleanup.lock().unwrap() = Instant::now() - CLEANUP_INTERVAL;
+        assert!(repository.claim_cleanup());
+        assert!(!repository.claim_cleanup());
+    }
+
+    #[test]
+    fn bucket_row_level_refills_by_the_database_clock() {
+        let config = BucketConfig::new(5, Duration::from_secs(1)).unwrap();
+        let updated_at = Utc::now();
+        let bucket = BucketRow {
+            tokens: 1.0,
+            updated_at,
+            now: updated_at + chrono::Duration::milliseconds(2500),
+        };
+        assert_eq!(bucket.level(&config), 3.5);
+
+        let bucket = BucketRow {
+            now: updated_at - chrono::Duration::seconds(1),
+            ..bucket
+        };
+        assert_eq!(bucket.level(&config), 1.0);
+    }
+}
@@ -0,0 +1,158 @@
+// src/lib/errors.rs
+
+// dependencies
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use sqlx::error::ErrorKind;
+use std::fmt::{Display, Formatter, Result};
+
+// enum type to represent the errors an API handler can return
+#[derive(Debug)]
+pub enum AppError {
+    NotFound,
+    BadRequest(String),
+    Forbidden(String),
+    Conflict(String),
+    PreconditionFailed(String),
+    TooManyRequests(String),
+    Database(sqlx::Error),
+}
+
+// struct type to represent the JSON problem body returned for an error, as described in RFC 9457
+#[derive(Serialize)]
+pub struct ProblemBody {
+    #[serde(rename = "type")]
+    problem_type: &'static str,
+    title: String,
+    status: u16,
+    detail: String,
+}
+
+// methods for the AppError type
+impl AppError {
+    // the status code the error is reported with
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            Self::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::Database(e) => match e {
+                sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+                sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+                sqlx::Error::Database(db_error) => match db_error.kind() {
+                    ErrorKind::UniqueViolation | ErrorKind::ForeignKeyViolation => {
+                        StatusCode::CONFLICT
+                    }
+                    ErrorKind::NotNullViolation | ErrorKind::CheckViolation => {
+                        StatusCode::UNPROCESSABLE_ENTITY
+                    }
+                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+                },
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+        }
+    }
+}
+
+// implement the Display trait for the AppError type
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::NotFound => f.write_str("The requested resource was not found."),
+            Self::BadRequest(detail)
+            | Self::Forbidden(detail)
+            | Self::Conflict(detail)
+            | Self::PreconditionFailed(detail)
+            | Self::TooManyRequests(detail) => f.write_str(detail),
+            Self::Database(e) => match self.status_code() {
+                StatusCode::NOT_FOUND => f.write_str("The requested resource was not found."),
+                StatusCode::SERVICE_UNAVAILABLE => {
+                    f.write_str("The database is currently unavailable.")
+                }
+                StatusCode::CONFLICT | StatusCode::UNPROCESSABLE_ENTITY => {
+                    f.write_fmt(format_args!("{}", e))
+                }
+                _ => f.write_str("An unexpected database error occurred."),
+            },
+        }
+    }
+}
+
+// implement the Error trait for the AppError type
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// implement the From trait for the AppError type, so sqlx errors can be returned with ?
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+// implement the IntoResponse trait for the AppError type, reporting the error as a JSON problem body
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status_code();
+        if status.is_server_error() {
+            tracing::error!(error = ?self, "Request failed.");
+        }
+
+        let problem_body = ProblemBody {
+            problem_type: "about:blank",
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail: self.to_string(),
+        };
+
+        (
+            status,
+            [(header::CONTENT_TYPE, "application/problem+json")],
+            Json(problem_body),
+        )
+            .into_response()
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn status_code_maps_sqlx_errors_to_http_status_codes() {
+        assert_eq!(
+            AppError::from(sqlx::Error::RowNotFound).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            AppError::from(sqlx::Error::PoolTimedOut).status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            AppError::from(sqlx::Error::ColumnNotFound("id".to_string())).status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn display_does_not_leak_internal_database_errors() {
+        let error = AppError::from(sqlx::Error::ColumnNotFound("secret_column".to_string()));
+        assert!(!error.to_string().contains("secret_column"));
+    }
+}
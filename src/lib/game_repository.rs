@@ -0,0 +1,370 @@
+// src/lib/game_repository.rs
+
+// dependencies
+use crate::errors::AppError;
+use crate::routes::day12::{Game, Move, Seats, Tile, BOARD_ID};
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+// trait to represent the storage behind the Day 12 games, so they survive a restart; the board at
+// /12/board is stored under BOARD_ID, and game sessions under their own ids
+#[async_trait]
+pub trait GameRepository: Debug + Send + Sync {
+    // store a game as it now stands, replacing its size, seats and moves in a single all or nothing step
+    async fn save(&self, id: Uuid, game: &Game) -> Result<(), AppError>;
+
+    // forget a game
+    async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+
+    // record how long ago each game was last played or read, for games which were not saved since
+    async fn touch(&self, idle_times: &[(Uuid, Duration)]) -> Result<(), AppError>;
+
+    // forget every game not played within the idle timeout, except the /12/board game
+    async fn prune(&self, idle_timeout: Duration) -> Result<(), AppError>;
+
+    // get every game saved within the idle timeout, along with the /12/board game however long ago it
+    // was played, forgetting the others
+    async fn load_active(&self, idle_timeout: Duration) -> Result<Vec<(Uuid, Game)>, AppError>;
+}
+
+// struct type to represent the Postgres implementation of the game repository
+#[derive(Clone, Debug)]
+pub struct PostgresGameRepository {
+    pool: PgPool,
+}
+
+// methods for the PostgresGameRepository type
+impl PostgresGameRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+// struct type to represent a row of the games table
+#[derive(Debug, sqlx::FromRow)]
+struct GameRow {
+    id: Uuid,
+    width: i32,
+    height: i32,
+    connect: i32,
+    strict: bool,
+    cookie_token: Option<String>,
+    milk_token: Option<String>,
+    tiles: Option<Vec<String>>,
+}
+
+// struct type to represent a row of the game_moves table
+#[derive(Debug, sqlx::FromRow)]
+struct MoveRow {
+    game_id: Uuid,
+    team: String,
+    column_index: i32,
+}
+
+// function to rebuild a game from its stored row, either from its tiles or by replaying its moves, or
+// None if they no longer make a valid game
+fn restore_game(row: GameRow, moves: &[Move]) -> Option<Game> {
+    let (width, height, connect) = (
+        row.width as usize,
+        row.height as usize,
+        row.connect as usize,
+    );
+    let mut game = match row.tiles {
+        Some(names) => {
+            let tiles: Vec<Tile> = names
+                .iter()
+                .map(|name| Tile::from_str(name).unwrap_or(Tile::Empty))
+                .collect();
+            Game::from_tiles(width, height, connect, &tiles)?
+        }
+        None => Game::from_moves(width, height, connect, moves)?,
+    };
+
+    if row.strict {
+        game.seats = Some(Seats {
+            cookie: row.cookie_token,
+            milk: row.milk_token,
+        });
+    }
+
+    Some(game)
+}
+
+// implement the GameRepository trait for the PostgresGameRepository type
+#[async_trait]
+impl GameRepository for PostgresGameRepository {
+    async fn save(&self, id: Uuid, game: &Game) -> Result<(), AppError> {
+        let (cookie_token, milk_token) = match &game.seats {
+            Some(seats) => (seats.cookie.clone(), seats.milk.clone()),
+            None => (None, None),
+        };
+
+        // a board filled without moves, such as a random one, is stored tile by tile instead
+        let filled_without_moves =
+            game.moves.is_empty() && game.board.iter().flatten().any(|t| *t != Tile::Empty);
+        let tiles: Option<Vec<String>> = filled_without_moves.then(|| {
+            game.board
+                .iter()
+                .flatten()
+                .map(|t| t.name().to_string())
+                .collect()
+        });
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("INSERT INTO games (id, width, height, connect, strict, cookie_token, milk_token, tiles, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW()) ON CONFLICT (id) DO UPDATE SET (width, height, connect, strict, cookie_token, milk_token, tiles, updated_at) = (EXCLUDED.width, EXCLUDED.height, EXCLUDED.connect, EXCLUDED.strict, EXCLUDED.cookie_token, EXCLUDED.milk_token, EXCLUDED.tiles, NOW())")
+            .bind(id)
+            .bind(game.width as i32)
+            .bind(game.height as i32)
+            .bind(game.connect as i32)
+            .bind(game.seats.is_some())
+            .bind(cookie_token)
+            .bind(milk_token)
+            .bind(tiles)
+            .execute(&mut *tx)
+            .await?;
+
+        // moves past the end of the game were undone, or belong to a board that has since been reset
+        sqlx::query("DELETE FROM game_moves WHERE game_id = $1 AND number > $2")
+            .bind(id)
+            .bind(game.moves.len() as i32)
+            .execute(&mut *tx)
+            .await?;
+
+        let numbers: Vec<i32> = (1..=game.moves.len() as i32).collect();
+        let teams: Vec<String> = game
+            .moves
+            .iter()
+            .map(|m| m.team.name().to_string())
+            .collect();
+        let columns: Vec<i32> = game.moves.iter().map(|m| m.column as i32).collect();
+        sqlx::query("INSERT INTO game_moves (game_id, number, team, column_index) SELECT $1, * FROM UNNEST($2::INT[], $3::TEXT[], $4::INT[]) ON CONFLICT (game_id, number) DO UPDATE SET (team, column_index) = (EXCLUDED.team, EXCLUDED.column_index)")
+            .bind(id)
+            .bind(numbers)
+            .bind(teams)
+            .bind(columns)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM games WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn touch(&self, idle_times: &[(Uuid, Duration)]) -> Result<(), AppError> {
+        let (ids, idle_secs): (Vec<Uuid>, Vec<f64>) = idle_times
+            .iter()
+            .map(|(id, idle)| (*id, idle.as_secs_f64()))
+            .unzip();
+
+        sqlx::query("UPDATE games SET updated_at = GREATEST(updated_at, NOW() - make_interval(secs => t.idle_secs)) FROM UNNEST($1::UUID[], $2::FLOAT8[]) AS t(id, idle_secs) WHERE games.id = t.id")
+            .bind(ids)
+            .bind(idle_secs)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn prune(&self, idle_timeout: Duration) -> Result<(), AppError> {
+        sqlx::query(
+            "DELETE FROM games WHERE id <> $1 AND updated_at < NOW() - make_interval(secs => $2)",
+        )
+        .bind(BOARD_ID)
+        .bind(idle_timeout.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn load_active(&self, idle_timeout: Duration) -> Result<Vec<(Uuid, Game)>, AppError> {
+        self.prune(idle_timeout).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let rows: Vec<GameRow> = sqlx::query_as(
+            "SELECT id, width, height, connect, strict, cookie_token, milk_token, tiles FROM games",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let move_rows: Vec<MoveRow> = sqlx::query_as(
+            "SELECT game_id, team, column_index FROM game_moves ORDER BY game_id, number",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let mut moves: HashMap<Uuid, Vec<Move>> = HashMap::new();
+        for row in move_rows {
+            let team = Tile::from_str(&row.team).unwrap_or(Tile::Empty);
+            moves.entry(row.game_id).or_default().push(Move {
+                team,
+                column: row.column_index as usize,
+            });
+        }
+
+        let games = rows
+            .into_iter()
+            .filter_map(|row| {
+                let id = row.id;
+                let game_moves = moves.remove(&id).unwrap_or_default();
+                match restore_game(row, &game_moves) {
+                    Some(game) => Some((id, game)),
+                    None => {
+                        tracing::warn!(%id, "Skipped a stored game whose moves are not valid.");
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Ok(games)
+    }
+}
+
+// struct type to represent the in-memory implementation of the game repository, for running the
+// Day 12 API without Postgres
+#[derive(Debug, Default)]
+pub struct InMemoryGameRepository {
+    games: RwLock<HashMap<Uuid, (Game, Instant)>>,
+}
+
+// implement the GameRepository trait for the InMemoryGameRepository type
+#[async_trait]
+impl GameRepository for InMemoryGameRepository {
+    async fn save(&self, id: Uuid, game: &Game) -> Result<(), AppError> {
+        let mut games = self.games.write().await;
+        games.insert(id, (game.clone(), Instant::now()));
+        Ok(())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        let mut games = self.games.write().await;
+        games.remove(&id);
+        Ok(())
+    }
+
+    async fn touch(&self, idle_times: &[(Uuid, Duration)]) -> Result<(), AppError> {
+        let mut games = self.games.write().await;
+        for (id, idle) in idle_times {
+            if let (Some((_, saved_at)), Some(played_at)) =
+                (games.get_mut(id), Instant::now().checked_sub(*idle))
+            {
+                *saved_at = (*saved_at).max(played_at);
+            }
+        }
+        Ok(())
+    }
+
+    async fn prune(&self, idle_timeout: Duration) -> Result<(), AppError> {
+        let mut games = self.games.write().await;
+        games.retain(|id, (_, saved_at)| *id == BOARD_ID || saved_at.elapsed() < idle_timeout);
+        Ok(())
+    }
+
+    async fn load_active(&self, idle_timeout: Duration) -> Result<Vec<(Uuid, Game)>, AppError> {
+        self.prune(idle_timeout).await?;
+
+        let games = self.games.read().await;
+        let active = games
+            .iter()
+            .map(|(id, (game, _))| (*id, game.clone()))
+            .collect();
+        Ok(active)
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[tokio::test]
+    async fn in_memory_load_active_keeps_the_board_and_forgets_idle_games() {
+        let repository = InMemoryGameRepository::default();
+        let mut game = Game::default();
+        game.make_move(Tile::Cookie, 2);
+
+        let session_id = Uuid::new_v4();
+        repository.save(BOARD_ID, &game).await.unwrap();
+        repository.save(session_id, &game).await.unwrap();
+
+        let active = repository
+            .load_active(Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(active.len(), 2);
+
+        let active = repository.load_active(Duration::ZERO).await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].0, BOARD_ID);
+        assert_eq!(active[0].1.moves, game.moves);
+    }
+
+    #[tokio::test]
+    async fn in_memory_prune_keeps_games_touched_within_the_idle_timeout() {
+        let repository = InMemoryGameRepository::default();
+        let (read_id, idle_id) = (Uuid::new_v4(), Uuid::new_v4());
+        repository.save(read_id, &Game::default()).await.unwrap();
+        repository.save(idle_id, &Game::default()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        repository
+            .touch(&[
+                (read_id, Duration::ZERO),
+                (idle_id, Duration::from_secs(60)),
+            ])
+            .await
+            .unwrap();
+        repository.prune(Duration::from_millis(25)).await.unwrap();
+
+        let active = repository
+            .load_active(Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].0, read_id);
+    }
+
+    #[test]
+    fn restore_game_replays_the_moves_and_keeps_the_seats() {
+        let row = GameRow {
+            id: Uuid::new_v4(),
+            width: 4,
+            height: 4,
+            connect: 4,
+            strict: true,
+            cookie_token: Some("cookie-token".to_string()),
+            milk_token: None,
+            tiles: None,
+        };
+        let moves = [Move {
+            team: Tile::Cookie,
+            column: 1,
+        }];
+
+        let game = restore_game(row, &moves).unwrap();
+        assert_eq!(game.get_tile(1, 0), Tile::Cookie);
+        assert_eq!(game.turn, Tile::Milk);
+        assert!(game.seats.unwrap().is_seated(Tile::Cookie, "cookie-token"));
+    }
+}
@@ -1,10 +1,22 @@
 // src/lib/lib.rs
 
 // module declarations
+pub mod bucket_repository;
+pub mod errors;
+pub mod game_repository;
+pub mod rate_limit;
+pub mod repository;
 pub mod routes;
 pub mod startup;
 pub mod telemetry;
+pub mod units;
 
 // re-exports
+pub use bucket_repository::*;
+pub use errors::*;
+pub use game_repository::*;
+pub use rate_limit::*;
+pub use repository::*;
 pub use startup::*;
 pub use telemetry::*;
+pub use units::*;
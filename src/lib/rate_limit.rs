@@ -0,0 +1,601 @@
+// src/lib/rate_limit.rs
+
+// dependencies
+use crate::errors::AppError;
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use futures::future::BoxFuture;
+use leaky_bucket::RateLimiter;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{Layer, Service};
+
+// header carrying the API key a client may identify itself with
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+// largest bucket capacity accepted by the /9/config endpoint
+const MAX_CAPACITY: usize = 1_000;
+
+// longest refill interval accepted by the /9/config endpoint
+const MAX_REFILL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// how often the buckets which have refilled completely are looked for and forgotten, at most
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+// struct type to represent the size of every client's bucket, and how quickly it refills
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BucketConfig {
+    pub capacity: usize,
+    pub refill_interval: Duration,
+}
+
+// struct type to represent the bucket configuration in the /9/config and /9/status endpoints
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BucketConfigBody {
+    pub capacity: usize,
+    pub refill_interval_ms: u64,
+}
+
+// struct type to represent the bucket of a single client; rather than being topped up on a timer, the
+// tokens it gained since it was last used are added when it is next looked at
+#[derive(Clone, Copy, Debug)]
+struct ClientBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+// struct type to represent a bucket for each client, each one forgotten once it has been idle for long
+// enough to have refilled completely, since a new bucket starts out full anyway; full buckets are looked
+// for every EVICTION_INTERVAL
+#[derive(Debug)]
+pub struct ClientBuckets {
+    buckets: HashMap<String, ClientBucket>,
+    config: BucketConfig,
+    evicted_at: Instant,
+}
+
+// struct type to represent the state of a client's bucket after a request has tried to take from it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitOutcome {
+    pub allowed: bool,
+    pub limit: usize,
+    pub remaining: usize,
+    pub reset: Duration,
+    pub retry_after: Duration,
+}
+
+// struct type to represent how many requests a route accepts in a burst, and how often it accepts one
+// more once the burst is spent
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitBudget {
+    pub max: usize,
+    pub interval: Duration,
+}
+
+// struct type to represent the leaky bucket of a client on a route, with the time it was last used
+#[derive(Debug)]
+struct RouteLimiter {
+    limiter: Arc<RateLimiter>,
+    budget: RateLimitBudget,
+    last_used: Instant,
+}
+
+// struct type to represent the leaky buckets of a RateLimitLayer, with the time the idle ones were last
+// forgotten
+#[derive(Debug)]
+struct RouteLimiters {
+    limiters: HashMap<(String, String), RouteLimiter>,
+    evicted_at: Instant,
+}
+
+// struct type to represent a tower layer which gives each client, on each route it is attached to, its
+// own leaky bucket, answering 429 Too Many Requests once the bucket has run dry; clients are told apart
+// like the Day 9 milk buckets do, by client_key. Attach it with Router::route_layer so the route a
+// request matched is known
+#[derive(Clone, Debug)]
+pub struct RateLimitLayer {
+    budget: RateLimitBudget,
+    route_budgets: HashMap<String, RateLimitBudget>,
+    api_keys: Arc<HashSet<String>>,
+    limiters: Arc<Mutex<RouteLimiters>>,
+}
+
+// struct type to represent the service wrapped by a RateLimitLayer
+#[derive(Clone, Debug)]
+pub struct RateLimitService<S> {
+    inner: S,
+    layer: RateLimitLayer,
+}
+
+// function to round a duration up to whole seconds, as used by the Retry-After header
+fn whole_seconds(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+// methods for the RateLimitBudget type
+impl RateLimitBudget {
+    pub const fn new(max: usize, interval: Duration) -> Self {
+        Self { max, interval }
+    }
+
+    // the time an empty leaky bucket holding the budget takes to fill up, after which it is no different
+    // from a new one
+    fn time_to_fill(&self) -> Duration {
+        self.interval.saturating_mul(self.max as u32)
+    }
+
+    // a full leaky bucket holding the budget
+    fn limiter(&self) -> RateLimiter {
+        RateLimiter::builder()
+            .initial(self.max)
+            .max(self.max)
+            .interval(self.interval)
+            .build()
+    }
+}
+
+// methods for the RateLimitLayer type
+impl RateLimitLayer {
+    // a layer giving every route the same budget
+    pub fn new(budget: RateLimitBudget) -> Self {
+        Self {
+            budget,
+            route_budgets: HashMap::new(),
+            api_keys: Arc::new(HashSet::new()),
+            limiters: Arc::new(Mutex::new(RouteLimiters {
+                limiters: HashMap::new(),
+                evicted_at: Instant::now(),
+            })),
+        }
+    }
+
+    // tell apart the clients sending one of the given keys in the X-Api-Key header, as client_key does
+    pub fn with_api_keys(mut self, api_keys: Arc<HashSet<String>>) -> Self {
+        self.api_keys = api_keys;
+        self
+    }
+
+    // give a route, written as it was registered with the router, a budget of its own
+    pub fn with_route_budget(mut self, route: &str, budget: RateLimitBudget) -> Self {
+        self.route_budgets.insert(route.to_string(), budget);
+        self
+    }
+
+    // the leaky bucket of the given client on the given route, created full the first time the client
+    // calls the route; every EVICTION_INTERVAL, the buckets idle for long enough to have refilled
+    // completely are forgotten
+    fn limiter(&self, route: &str, client: String) -> Arc<RateLimiter> {
+        let mut limiters = self
+            .limiters
+            .lock()
+            .expect("The rate limiter lock was poisoned.");
+        if limiters.evicted_at.elapsed() >= EVICTION_INTERVAL {
+            limiters
+                .limiters
+                .retain(|_, entry| entry.last_used.elapsed() < entry.budget.time_to_fill());
+            limiters.evicted_at = Instant::now();
+        }
+
+        let entry = limiters
+            .limiters
+            .entry((route.to_string(), client))
+            .or_insert_with(|| {
+                let budget = *self.route_budgets.get(route).unwrap_or(&self.budget);
+                RouteLimiter {
+                    limiter: Arc::new(budget.limiter()),
+                    budget,
+                    last_used: Instant::now(),
+                }
+            });
+        entry.last_used = Instant::now();
+        entry.limiter.clone()
+    }
+}
+
+// implement the Layer trait for the RateLimitLayer type
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+// implement the Service trait for the RateLimitService type, passing a request on to the route only if
+// its bucket has a token to spare
+impl<S> Service<Request> for RateLimitService<S>
+where
+    S: Service<Request, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_default();
+        let client = client_key(request.headers(), &self.layer.api_keys);
+        let limiter = self.layer.limiter(&route, client);
+
+        if !limiter.try_acquire(1) {
+            let retry_after = HeaderValue::from(whole_seconds(limiter.interval()));
+            let error = AppError::TooManyRequests(format!(
+                "Too many requests to {}, please try again later.",
+                route
+            ));
+            let response = ([(header::RETRY_AFTER, retry_after)], error).into_response();
+            return Box::pin(async move { Ok(response) });
+        }
+
+        Box::pin(self.inner.call(request))
+    }
+}
+
+// methods for the BucketConfig type
+impl BucketConfig {
+    // a configuration holding up to capacity tokens, refilled by one token per interval; returns the
+    // reason for rejecting it if either is out of range
+    pub fn new(capacity: usize, refill_interval: Duration) -> Result<Self, String> {
+        if !(1..=MAX_CAPACITY).contains(&capacity) {
+            return Err(format!(
+                "The capacity must be between 1 and {}.",
+                MAX_CAPACITY
+            ));
+        }
+        if refill_interval.is_zero() || refill_interval > MAX_REFILL_INTERVAL {
+            return Err(format!(
+                "The refill interval must be between 1 and {} milliseconds.",
+                MAX_REFILL_INTERVAL.as_millis()
+            ));
+        }
+
+        Ok(Self {
+            capacity,
+            refill_interval,
+        })
+    }
+
+    // the time a bucket holding the given tokens takes to fill up
+    pub(crate) fn time_to_fill(&self, tokens: f64) -> Duration {
+        let missing = (self.capacity as f64 - tokens).max(0.0);
+        self.refill_interval.mul_f64(missing)
+    }
+
+    // the time a bucket takes to fill up from empty, after which it is no different from a new one
+    pub(crate) fn idle_timeout(&self) -> Duration {
+        self.time_to_fill(0.0)
+    }
+
+    // the tokens in a bucket which held the given tokens when it was last updated, the given time ago
+    pub(crate) fn refilled(&self, tokens: f64, elapsed: Duration) -> f64 {
+        let refilled = elapsed.as_secs_f64() / self.refill_interval.as_secs_f64();
+        (tokens + refilled).min(self.capacity as f64)
+    }
+
+    // take a token from a bucket holding the given tokens, returning the tokens it is left with along
+    // with the outcome
+    pub(crate) fn take(&self, level: f64) -> (f64, RateLimitOutcome) {
+        let allowed = level >= 1.0;
+        let tokens = if allowed { level - 1.0 } else { level };
+
+        let outcome = RateLimitOutcome {
+            allowed,
+            limit: self.capacity,
+            remaining: tokens.floor() as usize,
+            reset: self.time_to_fill(tokens),
+            retry_after: self.refill_interval.mul_f64((1.0 - tokens).max(0.0)),
+        };
+        (tokens, outcome)
+    }
+}
+
+// implement the From trait to describe a bucket configuration in a response body
+impl From<BucketConfig> for BucketConfigBody {
+    fn from(config: BucketConfig) -> Self {
+        Self {
+            capacity: config.capacity,
+            refill_interval_ms: config.refill_interval.as_millis() as u64,
+        }
+    }
+}
+
+// methods for the ClientBucket type
+impl ClientBucket {
+    // the tokens in the bucket at the given time, including those it gained since it was last updated
+    fn level(&self, config: &BucketConfig, now: Instant) -> f64 {
+        config.refilled(self.tokens, now.saturating_duration_since(self.updated_at))
+    }
+}
+
+// methods for the ClientBuckets type
+impl ClientBuckets {
+    // buckets holding up to max tokens, each refilled by one token per interval
+    pub fn new(max: usize, interval: Duration) -> Self {
+        Self {
+            buckets: HashMap::new(),
+            config: BucketConfig {
+                capacity: max,
+                refill_interval: interval,
+            },
+            evicted_at: Instant::now(),
+        }
+    }
+
+    // forget every bucket which has refilled completely, unless that was done less than
+    // EVICTION_INTERVAL ago
+    fn evict_full(&mut self) {
+        let (config, now) = (self.config, Instant::now());
+        if now.duration_since(self.evicted_at) < EVICTION_INTERVAL {
+            return;
+        }
+        self.buckets
+            .retain(|_, bucket| bucket.level(&config, now) < config.capacity as f64);
+        self.evicted_at = now;
+    }
+
+    // the size of every bucket, and how quickly it refills
+    pub fn config(&self) -> BucketConfig {
+        self.config
+    }
+
+    // change the size and refill rate of every bucket; each client keeps the tokens it had, up to the
+    // new capacity
+    pub fn configure(&mut self, config: BucketConfig) {
+        let (old_config, now) = (self.config, Instant::now());
+        for bucket in self.buckets.values_mut() {
+            bucket.tokens = bucket.level(&old_config, now).min(config.capacity as f64);
+            bucket.updated_at = now;
+        }
+        self.config = config;
+    }
+
+    // the tokens in the client's bucket, which is full for a client not seen recently
+    pub fn level(&self, client: &str) -> f64 {
+        match self.buckets.get(client) {
+            Some(bucket) => bucket.level(&self.config, Instant::now()),
+            None => self.config.capacity as f64,
+        }
+    }
+
+    // take a token from the client's bucket, creating a full bucket for a client not seen recently
+    pub fn try_acquire(&mut self, client: &str) -> RateLimitOutcome {
+        let (config, now) = (self.config, Instant::now());
+        let level = self.level(client);
+        self.evict_full();
+
+        let (tokens, outcome) = config.take(level);
+        self.buckets.insert(
+            client.to_string(),
+            ClientBucket {
+                tokens,
+                updated_at: now,
+            },
+        );
+
+        outcome
+    }
+
+    // fill the client's bucket back up
+    pub fn refill(&mut self, client: &str) {
+        self.evict_full();
+        self.buckets.remove(client);
+    }
+}
+
+// methods for the RateLimitOutcome type
+impl RateLimitOutcome {
+    // the X-RateLimit headers describing the bucket, along with Retry-After once it has run dry; times
+    // are given in whole seconds, rounded up
+    pub fn headers(&self) -> HeaderMap {
+        let seconds = |duration: Duration| HeaderValue::from(whole_seconds(duration));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("x-ratelimit-limit"),
+            HeaderValue::from(self.limit),
+        );
+        headers.insert(
+            HeaderName::from_static("x-ratelimit-remaining"),
+            HeaderValue::from(self.remaining),
+        );
+        headers.insert(
+            HeaderName::from_static("x-ratelimit-reset"),
+            seconds(self.reset),
+        );
+        if !self.allowed {
+            headers.insert(header::RETRY_AFTER, seconds(self.retry_after));
+        }
+        headers
+    }
+}
+
+// function to identify the client making a request: by its API key when it is one of the keys handed
+// out, otherwise by its address, as added to the X-Forwarded-For header by the proxy in front of the app;
+// the proxy appends the address it saw, so only the last entry can be trusted, the others being
+// whatever the client sent. Requests without either share a single bucket
+pub fn client_key(headers: &HeaderMap, api_keys: &HashSet<String>) -> String {
+    let api_key = headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|key| api_keys.contains(*key));
+    if let Some(api_key) = api_key {
+        return format!("key:{}", api_key);
+    }
+
+    let forwarded_for = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .next_back()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .map(str::trim)
+        .filter(|address| !address.is_empty());
+
+    match forwarded_for {
+        Some(address) => format!("ip:{}", address),
+        None => "unknown".to_string(),
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn client_buckets_are_drained_and_refilled_separately() {
+        let mut buckets = ClientBuckets::new(2, Duration::from_secs(1));
+
+        assert!(buckets.try_acquire("ip:10.0.0.1").allowed);
+        let outcome = buckets.try_acquire("ip:10.0.0.1");
+        assert!(outcome.allowed);
+        assert_eq!(outcome.remaining, 0);
+        assert_eq!(outcome.headers()["x-ratelimit-reset"], "2");
+
+        let outcome = buckets.try_acquire("ip:10.0.0.1");
+        assert!(!outcome.allowed);
+        assert_eq!(outcome.headers()[header::RETRY_AFTER], "1");
+
+        assert!(buckets.try_acquire("ip:10.0.0.2").allowed);
+
+        buckets.refill("ip:10.0.0.1");
+        assert!(buckets.try_acquire("ip:10.0.0.1").allowed);
+    }
+
+    #[test]
+    fn configure_keeps_each_clients_tokens_up_to_the_new_capacity() {
+        let mut buckets = ClientBuckets::new(5, Duration::from_secs(60));
+        for _ in 0..2 {
+            buckets.try_acquire("key:santa");
+        }
+        assert_eq!(buckets.level("key:santa").floor(), 3.0);
+
+        buckets.configure(BucketConfig::new(2, Duration::from_secs(60)).unwrap());
+        assert_eq!(buckets.level("key:santa").floor(), 2.0);
+        assert_eq!(buckets.level("key:elf"), 2.0);
+    }
+
+    #[test]
+    fn client_buckets_forget_full_buckets_at_most_once_per_interval() {
+        let mut buckets = ClientBuckets::new(1, Duration::from_millis(1));
+        buckets.try_acquire("key:santa");
+        std::thread::sleep(Duration::from_millis(5));
+
+        buckets.try_acquire("key:elf");
+        assert_eq!(buckets.buckets.len(), 2);
+
+        buckets.evicted_at = Instant::now() - EVICTION_INTERVAL;
+        buckets.refill("key:elf");
+        assert!(buckets.buckets.is_empty());
+    }
+
+    #[test]
+    fn bucket_config_rejects_empty_buckets_and_out_of_range_intervals() {
+        assert!(BucketConfig::new(0, Duration::from_secs(1)).is_err());
+        assert!(BucketConfig::new(MAX_CAPACITY + 1, Duration::from_secs(1)).is_err());
+        assert!(BucketConfig::new(5, Duration::ZERO).is_err());
+        assert!(BucketConfig::new(5, Duration::from_secs(2 * 60 * 60)).is_err());
+        assert!(BucketConfig::new(5, Duration::from_millis(250)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn rate_limit_layer_gives_each_route_its_own_budget() {
+        use axum::{body::Body, http::StatusCode, routing::get, Router};
+        use tower::ServiceExt;
+
+        let layer = RateLimitLayer::new(RateLimitBudget::new(2, Duration::from_secs(60)))
+            .with_route_budget(
+                "/bulk",
+                RateLimitBudget::new(1, Duration::from_millis(1500)),
+            );
+        let router = Router::new()
+            .route("/quotes/:id", get(|| async { "quote" }))
+            .route("/bulk", get(|| async { "bulk" }))
+            .route_layer(layer);
+        let call = |uri: &'static str| {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            router.clone().oneshot(request)
+        };
+
+        assert_eq!(call("/quotes/1").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(call("/quotes/2").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(call("/bulk").await.unwrap().status(), StatusCode::OK);
+
+        let limited = call("/quotes/3").await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "60");
+
+        let limited = call("/bulk").await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "2");
+    }
+
+    #[tokio::test]
+    async fn rate_limit_layer_gives_each_client_its_own_budget_and_forgets_full_buckets() {
+        use axum::{body::Body, http::StatusCode, routing::get, Router};
+        use tower::ServiceExt;
+
+        let layer = RateLimitLayer::new(RateLimitBudget::new(1, Duration::from_millis(50)))
+            .with_api_keys(Arc::new(HashSet::from(["santa".to_string()])));
+        let router = Router::new()
+            .route("/quotes", get(|| async { "quotes" }))
+            .route_layer(layer.clone());
+        let call = |api_key: &'static str| {
+            let request = Request::builder()
+                .uri("/quotes")
+                .header(API_KEY_HEADER, api_key)
+                .body(Body::empty())
+                .unwrap();
+            router.clone().oneshot(request)
+        };
+
+        assert_eq!(call("grinch").await.unwrap().status(), StatusCode::OK);
+        let limited = call("elf").await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(call("santa").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(layer.limiters.lock().unwrap().limiters.len(), 2);
+
+        // full buckets are only forgotten once the eviction interval has passed
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(call("grinch").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(layer.limiters.lock().unwrap().limiters.len(), 2);
+
+        layer.limiters.lock().unwrap().evicted_at = Instant::now() - EVICTION_INTERVAL;
+        call("grinch").await.unwrap();
+        assert_eq!(layer.limiters.lock().unwrap().limiters.len(), 1);
+    }
+
+    #[test]
+    fn client_key_prefers_a_known_api_key_then_the_address_added_by_the_proxy() {
+        let api_keys = HashSet::from(["santa".to_string()]);
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_key(&headers, &api_keys), "unknown");
+
+        headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.1".parse().unwrap());
+        assert_eq!(client_key(&headers, &api_keys), "ip:10.0.0.1");
+
+        headers.insert(API_KEY_HEADER, "grinch".parse().unwrap());
+        assert_eq!(client_key(&headers, &api_keys), "ip:10.0.0.1");
+
+        headers.insert(API_KEY_HEADER, "santa".parse().unwrap());
+        assert_eq!(client_key(&headers, &api_keys), "key:santa");
+    }
+}
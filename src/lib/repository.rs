@@ -0,0 +1,1183 @@
+// src/lib/repository.rs
+
+// dependencies
+use crate::errors::AppError;
+use crate::routes::day19::{
+    ImportError, QuoteRecord, ResponseBody, RevisionResponseBody, SearchFilter, SearchResultBody,
+    TagCountBody, TrashResponseBody,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::stream::{self, BoxStream, StreamExt};
+use futures::TryStreamExt;
+use sqlx::{PgConnection, PgPool};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Debug;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+use uuid::Uuid;
+
+// enum type to represent the outcome of a conditional update of a quote
+#[derive(Debug)]
+pub enum UpdateOutcome {
+    Updated(ResponseBody),
+    NotFound,
+    VersionMismatch,
+}
+
+// trait to represent the storage behind the Day 19 quote book; removed quotes stay in the trash
+// until purged, and every version of a quote is kept in its revision history
+#[async_trait]
+pub trait QuoteRepository: Debug + Send + Sync {
+    // add a new quote
+    async fn create(
+        &self,
+        author: &str,
+        quote: &str,
+        tags: &[String],
+    ) -> Result<ResponseBody, AppError>;
+
+    // get the quote with the given id, unless it is in the trash
+    async fn get(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError>;
+
+    // replace the author and text of a quote as a new version, provided it is still at the expected
+    // version; the tags are only replaced when given
+    async fn update(
+        &self,
+        id: Uuid,
+        author: &str,
+        quote: &str,
+        tags: Option<&[String]>,
+        expected_version: Option<i32>,
+    ) -> Result<UpdateOutcome, AppError>;
+
+    // move a quote into the trash
+    async fn delete(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError>;
+
+    // move every quote into the trash
+    async fn truncate(&self) -> Result<(), AppError>;
+
+    // get the first quote outside the trash at or after the given id in id order, wrapping around to
+    // the start; for a random id, each quote is picked with a chance proportional to the gap between its
+    // id and the one before it, which evens out as the book grows but is not uniform
+    async fn pick(
+        &self,
+        from: Uuid,
+        author: Option<&str>,
+    ) -> Result<Option<ResponseBody>, AppError>;
+
+    // get a page of quotes, ordered by creation time
+    async fn list(&self, offset: i64, limit: i64) -> Result<Vec<ResponseBody>, AppError>;
+
+    // get every version of a quote, oldest first
+    async fn history(&self, id: Uuid) -> Result<Option<Vec<RevisionResponseBody>>, AppError>;
+
+    // restore a prior version of a quote as a new version
+    async fn revert(&self, id: Uuid, version: i32) -> Result<Option<ResponseBody>, AppError>;
+
+    // get every quote in the trash, most recently removed first
+    async fn trash(&self) -> Result<Vec<TrashResponseBody>, AppError>;
+
+    // take a quote back out of the trash
+    async fn restore(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError>;
+
+    // permanently delete a quote from the trash
+    async fn purge(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError>;
+
+    // permanently delete everything in the trash
+    async fn purge_all(&self) -> Result<(), AppError>;
+
+    // get every tag carried by a quote outside the trash, with the number of quotes carrying it
+    async fn tags(&self) -> Result<Vec<TagCountBody>, AppError>;
+
+    // get every quote outside the trash carrying the given tag, ordered by creation time
+    async fn quotes_by_tag(&self, tag: &str) -> Result<Vec<ResponseBody>, AppError>;
+
+    // get a page of quotes matching a search, best match first
+    async fn search(
+        &self,
+        filter: &SearchFilter,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<SearchResultBody>, AppError>;
+
+    // stream every quote, including those in the trash, ordered by creation time
+    fn export(&self) -> BoxStream<'static, Result<QuoteRecord, AppError>>;
+
+    // add every record in a single all or nothing step, returning the lines that could not be added
+    async fn import(&self, records: Vec<(u64, QuoteRecord)>) -> Result<Vec<ImportError>, AppError>;
+}
+
+// struct type to represent the Postgres implementation of the quote repository
+#[derive(Clone, Debug)]
+pub struct PostgresQuoteRepository {
+    pool: PgPool,
+}
+
+// methods for the PostgresQuoteRepository type
+impl PostgresQuoteRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+// column expression which collects the sorted tags of each row of the quotes table
+const TAGS_COLUMN: &str = "ARRAY(SELECT t.name FROM quote_tags qt JOIN tags t ON t.id = qt.tag_id WHERE qt.quote_id = quotes.id ORDER BY t.name) AS tags";
+
+// function to replace the tags of a quote, creating any tags not seen before
+async fn set_tags(conn: &mut PgConnection, id: Uuid, tags: &[String]) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM quote_tags WHERE quote_id = $1")
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("INSERT INTO tags (name) SELECT unnest($1::TEXT[]) ON CONFLICT (name) DO NOTHING")
+        .bind(tags)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO quote_tags (quote_id, tag_id) SELECT $1, id FROM tags WHERE name = ANY($2)",
+    )
+    .bind(id)
+    .bind(tags)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// function to record the current version of a quote in the revision history
+async fn record_revision(
+    conn: &mut PgConnection,
+    id: Uuid,
+    version: i32,
+    author: &str,
+    quote: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO quote_revisions (quote_id, version, author, quote) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(id)
+    .bind(version)
+    .bind(author)
+    .bind(quote)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// implement the QuoteRepository trait for the PostgresQuoteRepository type
+#[async_trait]
+impl QuoteRepository for PostgresQuoteRepository {
+    async fn create(
+        &self,
+        author: &str,
+        quote: &str,
+        tags: &[String],
+    ) -> Result<ResponseBody, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let id = Uuid::new_v4();
+        sqlx::query("INSERT INTO quotes (id, author, quote, created_at) VALUES ($1, $2, $3, $4)")
+            .bind(id)
+            .bind(author)
+            .bind(quote)
+            .bind(Utc::now())
+            .execute(&mut *tx)
+            .await?;
+        set_tags(&mut tx, id, tags).await?;
+
+        let created: ResponseBody = sqlx::query_as(&format!(
+            "SELECT *, {TAGS_COLUMN} FROM quotes WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        record_revision(
+            &mut tx,
+            created.id,
+            created.version,
+            &created.author,
+            &created.quote,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError> {
+        let quote = sqlx::query_as(&format!(
+            "SELECT *, {TAGS_COLUMN} FROM quotes WHERE id = $1 AND deleted_at IS NULL"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(quote)
+    }
+
+    async fn update(
+        &self,
+        id: Uuid,
+        author: &str,
+        quote: &str,
+        tags: Option<&[String]>,
+        expected_version: Option<i32>,
+    ) -> Result<UpdateOutcome, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let updated: Option<ResponseBody> = sqlx::query_as(&format!("UPDATE quotes SET (author, quote, version) = ($1, $2, version+1) WHERE id = $3 AND deleted_at IS NULL AND ($4::INT IS NULL OR version = $4) RETURNING *, {TAGS_COLUMN}"))
+            .bind(author)
+            .bind(quote)
+            .bind(id)
+            .bind(expected_version)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        match updated {
+            Some(mut updated) => {
+                if let Some(tags) = tags {
+                    set_tags(&mut tx, id, tags).await?;
+                    updated.tags = tags.to_vec();
+                }
+
+                record_revision(
+                    &mut tx,
+                    updated.id,
+                    updated.version,
+                    &updated.author,
+                    &updated.quote,
+                )
+                .await?;
+                tx.commit().await?;
+
+                Ok(UpdateOutcome::Updated(updated))
+            }
+            None => {
+                // the update matched nothing, either the quote is missing or its version has moved on
+                let quote_exists =
+                    sqlx::query("SELECT id FROM quotes WHERE id = $1 AND deleted_at IS NULL")
+                        .bind(id)
+                        .fetch_optional(&mut *tx)
+                        .await?
+                        .is_some();
+
+                if quote_exists {
+                    Ok(UpdateOutcome::VersionMismatch)
+                } else {
+                    Ok(UpdateOutcome::NotFound)
+                }
+            }
+        }
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError> {
+        let deleted = sqlx::query_as(&format!(
+            "UPDATE quotes SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL RETURNING *, {TAGS_COLUMN}"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(deleted)
+    }
+
+    async fn truncate(&self) -> Result<(), AppError> {
+        sqlx::query("UPDATE quotes SET deleted_at = now() WHERE deleted_at IS NULL")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list(&self, offset: i64, limit: i64) -> Result<Vec<ResponseBody>, AppError> {
+        let quotes = sqlx::query_as(&format!("SELECT *, {TAGS_COLUMN} FROM quotes WHERE deleted_at IS NULL ORDER BY created_at ASC, id ASC LIMIT $1 OFFSET $2"))
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(quotes)
+    }
+
+    async fn pick(
+        &self,
+        from: Uuid,
+        author: Option<&str>,
+    ) -> Result<Option<ResponseBody>, AppError> {
+        // both halves walk an index, the primary key or the author index, and the second only runs if
+        // the first is empty
+        let query = match author {
+            Some(_) => format!(
+                "(SELECT *, {TAGS_COLUMN} FROM quotes \
+                     WHERE lower(author) = lower($2) AND id >= $1 AND deleted_at IS NULL \
+                     ORDER BY lower(author) ASC, id ASC LIMIT 1) \
+                 UNION ALL \
+                 (SELECT *, {TAGS_COLUMN} FROM quotes \
+                     WHERE lower(author) = lower($2) AND deleted_at IS NULL \
+                     ORDER BY lower(author) ASC, id ASC LIMIT 1) \
+                 LIMIT 1"
+            ),
+            None => format!(
+                "(SELECT *, {TAGS_COLUMN} FROM quotes \
+                     WHERE id >= $1 AND deleted_at IS NULL \
+                     ORDER BY id ASC LIMIT 1) \
+                 UNION ALL \
+                 (SELECT *, {TAGS_COLUMN} FROM quotes \
+                     WHERE deleted_at IS NULL \
+                     ORDER BY id ASC LIMIT 1) \
+                 LIMIT 1"
+            ),
+        };
+        let picked = sqlx::query_as(&query)
+            .bind(from)
+            .bind(author)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(picked)
+    }
+
+    async fn history(&self, id: Uuid) -> Result<Option<Vec<RevisionResponseBody>>, AppError> {
+        if self.get(id).await?.is_none() {
+            return Ok(None);
+        }
+
+        let revisions = sqlx::query_as(
+            "SELECT * FROM quote_revisions WHERE quote_id = $1 ORDER BY version ASC",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Some(revisions))
+    }
+
+    async fn revert(&self, id: Uuid, version: i32) -> Result<Option<ResponseBody>, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let revision: Option<RevisionResponseBody> = sqlx::query_as("SELECT r.* FROM quote_revisions r JOIN quotes q ON q.id = r.quote_id WHERE r.quote_id = $1 AND r.version = $2 AND q.deleted_at IS NULL")
+            .bind(id)
+            .bind(version)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let revision = match revision {
+            Some(revision) => revision,
+            None => return Ok(None),
+        };
+
+        let reverted: ResponseBody = sqlx::query_as(&format!("UPDATE quotes SET (author, quote, version) = ($1, $2, version+1) WHERE id = $3 AND deleted_at IS NULL RETURNING *, {TAGS_COLUMN}"))
+            .bind(&revision.author)
+            .bind(&revision.quote)
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        record_revision(
+            &mut tx,
+            reverted.id,
+            reverted.version,
+            &reverted.author,
+            &reverted.quote,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(reverted))
+    }
+
+    async fn trash(&self) -> Result<Vec<TrashResponseBody>, AppError> {
+        let trashed = sqlx::query_as(&format!(
+            "SELECT *, {TAGS_COLUMN} FROM quotes WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC, id ASC"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(trashed)
+    }
+
+    async fn restore(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError> {
+        let restored = sqlx::query_as(&format!(
+            "UPDATE quotes SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL RETURNING *, {TAGS_COLUMN}"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(restored)
+    }
+
+    async fn purge(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError> {
+        let purged = sqlx::query_as(&format!(
+            "DELETE FROM quotes WHERE id = $1 AND deleted_at IS NOT NULL RETURNING *, {TAGS_COLUMN}"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(purged)
+    }
+
+    async fn purge_all(&self) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM quotes WHERE deleted_at IS NOT NULL")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        filter: &SearchFilter,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<SearchResultBody>, AppError> {
+        let results = sqlx::query_as(&format!(
+            "SELECT quotes.*, {TAGS_COLUMN}, \
+                 ts_rank(search_vector, query) AS rank, \
+                 ts_headline('english', quote, query, 'StartSel=<mark>, StopSel=</mark>') AS snippet \
+             FROM quotes, websearch_to_tsquery('english', coalesce($1, '')) AS query \
+             WHERE deleted_at IS NULL \
+                 AND ($1::TEXT IS NULL OR search_vector @@ query) \
+                 AND ($2::TEXT IS NULL OR lower(author) = lower($2)) \
+             ORDER BY rank DESC, created_at ASC, id ASC \
+             LIMIT $3 OFFSET $4"
+        ))
+        .bind(&filter.q)
+        .bind(&filter.author)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn tags(&self) -> Result<Vec<TagCountBody>, AppError> {
+        let tags = sqlx::query_as(
+            "SELECT t.name AS tag, count(*) AS count \
+             FROM tags t \
+                 JOIN quote_tags qt ON qt.tag_id = t.id \
+                 JOIN quotes q ON q.id = qt.quote_id \
+             WHERE q.deleted_at IS NULL \
+             GROUP BY t.name \
+             ORDER BY count DESC, tag ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tags)
+    }
+
+    async fn quotes_by_tag(&self, tag: &str) -> Result<Vec<ResponseBody>, AppError> {
+        let quotes = sqlx::query_as(&format!(
+            "SELECT quotes.*, {TAGS_COLUMN} \
+             FROM quotes \
+                 JOIN quote_tags qt ON qt.quote_id = quotes.id \
+                 JOIN tags t ON t.id = qt.tag_id \
+             WHERE t.name = $1 AND quotes.deleted_at IS NULL \
+             ORDER BY created_at ASC, id ASC"
+        ))
+        .bind(tag)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(quotes)
+    }
+
+    fn export(&self) -> BoxStream<'static, Result<QuoteRecord, AppError>> {
+        let pool = self.pool.clone();
+        let (sender, receiver) = mpsc::channel(32);
+
+        // read the rows in a background task, so the stream does not borrow the pool
+        tokio::spawn(async move {
+            let query =
+                format!("SELECT *, {TAGS_COLUMN} FROM quotes ORDER BY created_at ASC, id ASC");
+            let mut rows = sqlx::query_as::<_, QuoteRecord>(&query).fetch(&pool);
+
+            loop {
+                let record = match rows.try_next().await {
+                    Ok(Some(record)) => Ok(record),
+                    Ok(None) => break,
+                    Err(e) => Err(AppError::from(e)),
+                };
+
+                let failed = record.is_err();
+                if sender.send(record).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Box::pin(stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|record| (record, receiver))
+        }))
+    }
+
+    async fn import(&self, records: Vec<(u64, QuoteRecord)>) -> Result<Vec<ImportError>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let mut errors = Vec::new();
+
+        for (line, record) in records {
+            let inserted = sqlx::query(
+                "INSERT INTO quotes (id, author, quote, created_at, version, deleted_at) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (id) DO NOTHING RETURNING id",
+            )
+            .bind(record.id)
+            .bind(&record.author)
+            .bind(&record.quote)
+            .bind(record.created_at)
+            .bind(record.version)
+            .bind(record.deleted_at)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if inserted.is_none() {
+                errors.push(ImportError {
+                    line,
+                    error: format!("a quote with id {} already exists", record.id),
+                });
+                continue;
+            }
+
+            record_revision(
+                &mut tx,
+                record.id,
+                record.version,
+                &record.author,
+                &record.quote,
+            )
+            .await?;
+
+            if !record.tags.is_empty() {
+                set_tags(&mut tx, record.id, &record.tags).await?;
+            }
+        }
+
+        if errors.is_empty() {
+            tx.commit().await?;
+        } else {
+            tx.rollback().await?;
+        }
+
+        Ok(errors)
+    }
+}
+
+// struct type to represent the in-memory implementation of the quote repository, for running the
+// Day 19 API without Postgres; search matches whole words rather than stemmed lexemes
+#[derive(Debug, Default)]
+pub struct InMemoryQuoteRepository {
+    book: Arc<RwLock<InMemoryQuoteBook>>,
+}
+
+// struct type to represent the contents of the in-memory quote repository; the tags of each quote are
+// kept in the tags map, and only copied into its record for an export
+#[derive(Debug, Default)]
+struct InMemoryQuoteBook {
+    quotes: Vec<QuoteRecord>,
+    revisions: HashMap<Uuid, Vec<RevisionResponseBody>>,
+    tags: HashMap<Uuid, Vec<String>>,
+}
+
+// methods for the InMemoryQuoteBook type
+impl InMemoryQuoteBook {
+    // find a quote, either outside or inside the trash
+    fn find_mut(&mut self, id: Uuid, trashed: bool) -> Option<&mut QuoteRecord> {
+        self.quotes
+            .iter_mut()
+            .find(|q| q.id == id && q.deleted_at.is_some() == trashed)
+    }
+
+    // every quote outside the trash, ordered by creation time
+    fn active(&self) -> Vec<&QuoteRecord> {
+        let mut active: Vec<&QuoteRecord> = self
+            .quotes
+            .iter()
+            .filter(|q| q.deleted_at.is_none())
+            .collect();
+        active.sort_by_key(|q| (q.created_at, q.id));
+        active
+    }
+
+    // build the API response body for a quote, along with its tags
+    fn response(&self, record: &QuoteRecord) -> ResponseBody {
+        let mut response = ResponseBody::from(record);
+        response.tags = self.tags.get(&record.id).cloned().unwrap_or_default();
+        response
+    }
+
+    // forget everything about the given quotes
+    fn forget(&mut self, ids: &[Uuid]) {
+        self.quotes.retain(|q| !ids.contains(&q.id));
+        for id in ids {
+            self.revisions.remove(id);
+            self.tags.remove(id);
+        }
+    }
+
+    // record the current version of a quote in the revision history
+    fn record_revision(&mut self, record: &QuoteRecord) {
+        self.revisions
+            .entry(record.id)
+            .or_default()
+            .push(RevisionResponseBody {
+                version: record.version,
+                author: record.author.clone(),
+                quote: record.quote.clone(),
+                revised_at: Utc::now(),
+            });
+    }
+
+    // replace the author and text of a quote as a new version
+    fn revise(&mut self, id: Uuid, author: &str, quote: &str) -> Option<ResponseBody> {
+        let record = self.find_mut(id, false)?;
+        record.author = author.to_string();
+        record.quote = quote.to_string();
+        record.version += 1;
+        let record = record.clone();
+        self.record_revision(&record);
+        Some(self.response(&record))
+    }
+}
+
+// function to split text into lowercase words
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+// function to wrap every word of the text found in the search terms in <mark> tags
+fn highlight(text: &str, terms: &HashSet<String>) -> String {
+    let mut snippet = String::new();
+    let mut word_start = None;
+
+    let push_word = |snippet: &mut String, word: &str| {
+        if terms.contains(&word.to_lowercase()) {
+            snippet.push_str("<mark>");
+            snippet.push_str(word);
+            snippet.push_str("</mark>");
+        } else {
+            snippet.push_str(word);
+        }
+    };
+
+    for (i, c) in text.char_indices() {
+        if c.is_alphanumeric() {
+            word_start.get_or_insert(i);
+        } else {
+            if let Some(start) = word_start.take() {
+                push_word(&mut snippet, &text[start..i]);
+            }
+            snippet.push(c);
+        }
+    }
+    if let Some(start) = word_start {
+        push_word(&mut snippet, &text[start..]);
+    }
+
+    snippet
+}
+
+// implement the QuoteRepository trait for the InMemoryQuoteRepository type
+#[async_trait]
+impl QuoteRepository for InMemoryQuoteRepository {
+    async fn create(
+        &self,
+        author: &str,
+        quote: &str,
+        tags: &[String],
+    ) -> Result<ResponseBody, AppError> {
+        let mut book = self.book.write().await;
+        let record = QuoteRecord {
+            id: Uuid::new_v4(),
+            author: author.to_string(),
+            quote: quote.to_string(),
+            created_at: Utc::now(),
+            version: 1,
+            deleted_at: None,
+            tags: Vec::new(),
+        };
+        book.record_revision(&record);
+        book.quotes.push(record.clone());
+        book.tags.insert(record.id, tags.to_vec());
+
+        Ok(book.response(&record))
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError> {
+        let book = self.book.read().await;
+        Ok(book
+            .active()
+            .into_iter()
+            .find(|q| q.id == id)
+            .map(|q| book.response(q)))
+    }
+
+    async fn update(
+        &self,
+        id: Uuid,
+        author: &str,
+        quote: &str,
+        tags: Option<&[String]>,
+        expected_version: Option<i32>,
+    ) -> Result<UpdateOutcome, AppError> {
+        let mut book = self.book.write().await;
+        let current_version = match book.find_mut(id, false) {
+            Some(record) => record.version,
+            None => return Ok(UpdateOutcome::NotFound),
+        };
+
+        if expected_version.is_some_and(|v| v != current_version) {
+            return Ok(UpdateOutcome::VersionMismatch);
+        }
+
+        if let Some(tags) = tags {
+            book.tags.insert(id, tags.to_vec());
+        }
+
+        match book.revise(id, author, quote) {
+            Some(updated) => Ok(UpdateOutcome::Updated(updated)),
+            None => Ok(UpdateOutcome::NotFound),
+        }
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError> {
+        let mut book = self.book.write().await;
+        let deleted = book.find_mut(id, false).map(|record| {
+            record.deleted_at = Some(Utc::now());
+            record.clone()
+        });
+
+        Ok(deleted.map(|record| book.response(&record)))
+    }
+
+    async fn truncate(&self) -> Result<(), AppError> {
+        let mut book = self.book.write().await;
+        let deleted_at = Utc::now();
+        book.quotes
+            .iter_mut()
+            .filter(|q| q.deleted_at.is_none())
+            .for_each(|q| q.deleted_at = Some(deleted_at));
+
+        Ok(())
+    }
+
+    async fn list(&self, offset: i64, limit: i64) -> Result<Vec<ResponseBody>, AppError> {
+        let book = self.book.read().await;
+        Ok(book
+            .active()
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|q| book.response(q))
+            .collect())
+    }
+
+    async fn pick(
+        &self,
+        from: Uuid,
+        author: Option<&str>,
+    ) -> Result<Option<ResponseBody>, AppError> {
+        let book = self.book.read().await;
+        let mut candidates: Vec<&QuoteRecord> = book
+            .active()
+            .into_iter()
+            .filter(|q| author.is_none_or(|a| a.to_lowercase() == q.author.to_lowercase()))
+            .collect();
+        candidates.sort_by_key(|q| q.id);
+
+        let picked = candidates
+            .iter()
+            .find(|q| q.id >= from)
+            .or_else(|| candidates.first());
+
+        Ok(picked.map(|q| book.response(q)))
+    }
+
+    async fn history(&self, id: Uuid) -> Result<Option<Vec<RevisionResponseBody>>, AppError> {
+        let mut book = self.book.write().await;
+        if book.find_mut(id, false).is_none() {
+            return Ok(None);
+        }
+
+        let mut revisions = book.revisions.get(&id).cloned().unwrap_or_default();
+        revisions.sort_by_key(|r| r.version);
+        Ok(Some(revisions))
+    }
+
+    async fn revert(&self, id: Uuid, version: i32) -> Result<Option<ResponseBody>, AppError> {
+        let mut book = self.book.write().await;
+        if book.find_mut(id, false).is_none() {
+            return Ok(None);
+        }
+
+        let revision = book
+            .revisions
+            .get(&id)
+            .and_then(|revisions| revisions.iter().find(|r| r.version == version))
+            .cloned();
+
+        Ok(revision.and_then(|r| book.revise(id, &r.author, &r.quote)))
+    }
+
+    async fn trash(&self) -> Result<Vec<TrashResponseBody>, AppError> {
+        let book = self.book.read().await;
+        let mut trashed: Vec<&QuoteRecord> = book
+            .quotes
+            .iter()
+            .filter(|q| q.deleted_at.is_some())
+            .collect();
+        trashed.sort_by_key(|q| (std::cmp::Reverse(q.deleted_at), q.id));
+
+        Ok(trashed
+            .into_iter()
+            .filter_map(|q| {
+                q.deleted_at.map(|deleted_at| TrashResponseBody {
+                    quote: book.response(q),
+                    deleted_at,
+                })
+            })
+            .collect())
+    }
+
+    async fn restore(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError> {
+        let mut book = self.book.write().await;
+        let restored = book.find_mut(id, true).map(|record| {
+            record.deleted_at = None;
+            record.clone()
+        });
+
+        Ok(restored.map(|record| book.response(&record)))
+    }
+
+    async fn purge(&self, id: Uuid) -> Result<Option<ResponseBody>, AppError> {
+        let mut book = self.book.write().await;
+        let purged = book.find_mut(id, true).map(|record| record.clone());
+        let purged = purged.map(|record| book.response(&record));
+        if purged.is_some() {
+            book.forget(&[id]);
+        }
+
+        Ok(purged)
+    }
+
+    async fn purge_all(&self) -> Result<(), AppError> {
+        let mut book = self.book.write().await;
+        let purged: Vec<Uuid> = book
+            .quotes
+            .iter()
+            .filter(|q| q.deleted_at.is_some())
+            .map(|q| q.id)
+            .collect();
+        book.forget(&purged);
+
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        filter: &SearchFilter,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<SearchResultBody>, AppError> {
+        let book = self.book.read().await;
+        let terms: HashSet<String> = filter
+            .q
+            .as_deref()
+            .map(words)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+
+        let mut results: Vec<(f32, &QuoteRecord)> = book
+            .active()
+            .into_iter()
+            .filter(|q| {
+                filter
+                    .author
+                    .as_ref()
+                    .is_none_or(|a| a.to_lowercase() == q.author.to_lowercase())
+            })
+            .filter_map(|q| {
+                if terms.is_empty() {
+                    return Some((0.0, q));
+                }
+                let quote_words = words(&format!("{} {}", q.author, q.quote));
+                let matches = quote_words.iter().filter(|w| terms.contains(*w)).count();
+                let all_terms_found = terms.iter().all(|t| quote_words.contains(t));
+                all_terms_found.then(|| (matches as f32 / quote_words.len() as f32, q))
+            })
+            .collect();
+
+        // the sort is stable, so equally ranked quotes stay in order of creation
+        results.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        Ok(results
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(rank, q)| SearchResultBody {
+                quote: book.response(q),
+                rank,
+                snippet: highlight(&q.quote, &terms),
+            })
+            .collect())
+    }
+
+    async fn tags(&self) -> Result<Vec<TagCountBody>, AppError> {
+        let book = self.book.read().await;
+        let mut counts: BTreeMap<&str, i64> = BTreeMap::new();
+        for q in book.active() {
+            for tag in book.tags.get(&q.id).into_iter().flatten() {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+
+        let mut tags: Vec<TagCountBody> = counts
+            .into_iter()
+            .map(|(tag, count)| TagCountBody {
+                tag: tag.to_string(),
+                count,
+            })
+            .collect();
+        // the sort is stable, so equally common tags stay in alphabetical order
+        tags.sort_by_key(|t| std::cmp::Reverse(t.count));
+
+        Ok(tags)
+    }
+
+    async fn quotes_by_tag(&self, tag: &str) -> Result<Vec<ResponseBody>, AppError> {
+        let book = self.book.read().await;
+        Ok(book
+            .active()
+            .into_iter()
+            .filter(|q| {
+                book.tags
+                    .get(&q.id)
+                    .is_some_and(|t| t.iter().any(|t| t == tag))
+            })
+            .map(|q| book.response(q))
+            .collect())
+    }
+
+    fn export(&self) -> BoxStream<'static, Result<QuoteRecord, AppError>> {
+        // the stream waits its turn behind any writer, then takes a snapshot of the whole book
+        let book = Arc::clone(&self.book);
+        let records = stream::once(async move {
+            let book = book.read().await;
+            let mut records = book.quotes.clone();
+            for record in &mut records {
+                record.tags = book.tags.get(&record.id).cloned().unwrap_or_default();
+            }
+            records.sort_by_key(|q| (q.created_at, q.id));
+            records
+        });
+
+        Box::pin(records.flat_map(|records| stream::iter(records.into_iter().map(Ok))))
+    }
+
+    async fn import(&self, records: Vec<(u64, QuoteRecord)>) -> Result<Vec<ImportError>, AppError> {
+        let mut book = self.book.write().await;
+        let mut known: HashSet<Uuid> = book.quotes.iter().map(|q| q.id).collect();
+
+        let errors: Vec<ImportError> = records
+            .iter()
+            .filter(|(_, record)| !known.insert(record.id))
+            .map(|(line, record)| ImportError {
+                line: *line,
+                error: format!("a quote with id {} already exists", record.id),
+            })
+            .collect();
+
+        if errors.is_empty() {
+            for (_, mut record) in records {
+                book.record_revision(&record);
+                book.tags
+                    .insert(record.id, std::mem::take(&mut record.tags));
+                book.quotes.push(record);
+            }
+        }
+
+        Ok(errors)
+    }
+}
+
+// implement the From trait to convert a quote record into the API response body
+impl From<&QuoteRecord> for ResponseBody {
+    fn from(record: &QuoteRecord) -> Self {
+        Self {
+            id: record.id,
+            author: record.author.clone(),
+            quote: record.quote.clone(),
+            created_at: record.created_at,
+            version: record.version,
+            tags: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[tokio::test]
+    async fn in_memory_export_waits_for_a_writer_instead_of_failing() {
+        let repository = InMemoryQuoteRepository::default();
+        repository.create("Santa", "Ho ho ho!", &[]).await.unwrap();
+
+        let writer = repository.book.write().await;
+        let exporting = tokio::spawn(repository.export().try_collect::<Vec<_>>());
+        tokio::task::yield_now().await;
+        drop(writer);
+
+        let records = exporting.await.unwrap().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].author, "Santa");
+    }
+
+    #[tokio::test]
+    async fn in_memory_export_and_import_carry_the_tags() {
+        let repository = InMemoryQuoteRepository::default();
+        let tags = vec!["christmas".to_string()];
+        repository
+            .create("Santa", "Ho ho ho!", &tags)
+            .await
+            .unwrap();
+
+        let records: Vec<QuoteRecord> = repository.export().try_collect().await.unwrap();
+        assert_eq!(records[0].tags, tags);
+
+        let restored = InMemoryQuoteRepository::default();
+        let errors = restored
+            .import(records.into_iter().map(|r| (1, r)).collect())
+            .await
+            .unwrap();
+        assert!(errors.is_empty());
+        let tagged = restored.quotes_by_tag("christmas").await.unwrap();
+        assert_eq!(tagged[0].tags, tags);
+    }
+
+    #[tokio::test]
+    async fn in_memory_update_bumps_version_and_rejects_stale_versions() {
+        let repository = InMemoryQuoteRepository::default();
+        let created = repository.create("Santa", "Ho ho ho!", &[]).await.unwrap();
+
+        let updated = repository
+            .update(created.id, "Santa", "Ho ho hum.", None, Some(1))
+            .await
+            .unwrap();
+        assert!(matches!(updated, UpdateOutcome::Updated(ref q) if q.version == 2));
+
+        let stale = repository
+            .update(created.id, "Santa", "Ho!", None, Some(1))
+            .await
+            .unwrap();
+        assert!(matches!(stale, UpdateOutcome::VersionMismatch));
+
+        let history = repository.history(created.id).await.unwrap().unwrap();
+        assert_eq!(history.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn in_memory_delete_moves_quote_to_trash_until_restored() {
+        let repository = InMemoryQuoteRepository::default();
+        let created = repository.create("Santa", "Ho ho ho!", &[]).await.unwrap();
+
+        repository.delete(created.id).await.unwrap().unwrap();
+        assert!(repository.get(created.id).await.unwrap().is_none());
+        assert_eq!(repository.trash().await.unwrap().len(), 1);
+
+        repository.restore(created.id).await.unwrap().unwrap();
+        assert!(repository.get(created.id).await.unwrap().is_some());
+        assert!(repository.trash().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn in_memory_import_is_all_or_nothing() {
+        let repository = InMemoryQuoteRepository::default();
+        let created = repository.create("Santa", "Ho ho ho!", &[]).await.unwrap();
+        let record = |id| QuoteRecord {
+            id,
+            author: "Rudolph".to_string(),
+            quote: "Shine bright!".to_string(),
+            created_at: Utc::now(),
+            version: 1,
+            deleted_at: None,
+            tags: Vec::new(),
+        };
+
+        let errors = repository
+            .import(vec![(1, record(Uuid::new_v4())), (2, record(created.id))])
+            .await
+            .unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 2);
+        assert_eq!(repository.list(0, 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn in_memory_tags_count_only_quotes_outside_the_trash() {
+        let repository = InMemoryQuoteRepository::default();
+        let tags = vec!["cheer".to_string(), "christmas".to_string()];
+        let first = repository
+            .create("Santa", "Ho ho ho!", &tags)
+            .await
+            .unwrap();
+        repository
+            .create("Rudolph", "Shine bright!", &tags[1..])
+            .await
+            .unwrap();
+        repository.delete(first.id).await.unwrap();
+
+        let counts = repository.tags().await.unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].tag, "christmas");
+        assert_eq!(counts[0].count, 1);
+        assert!(repository.quotes_by_tag("cheer").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn in_memory_pick_wraps_around_to_the_lowest_id() {
+        let repository = InMemoryQuoteRepository::default();
+        let santa = repository.create("Santa", "Ho ho ho!", &[]).await.unwrap();
+        let rudolph = repository
+            .create("Rudolph", "Shine bright!", &[])
+            .await
+            .unwrap();
+        let lowest = santa.id.min(rudolph.id);
+
+        let picked = repository.pick(Uuid::max(), None).await.unwrap().unwrap();
+        assert_eq!(picked.id, lowest);
+
+        let picked = repository
+            .pick(Uuid::nil(), Some("rudolph"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(picked.id, rudolph.id);
+
+        assert!(repository
+            .pick(Uuid::nil(), Some("Grinch"))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn highlight_marks_matching_words_case_insensitively() {
+        let terms = HashSet::from(["ho".to_string()]);
+        assert_eq!(
+            highlight("Ho, hold on!", &terms),
+            "<mark>Ho</mark>, hold on!"
+        );
+    }
+}
@@ -3,19 +3,440 @@
 // dependencies
 use crate::startup::AppState;
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Json, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
 };
+use axum_extra::headers::{authorization::Bearer, Authorization};
+use axum_extra::TypedHeader;
 use axum_macros::debug_handler;
+use futures::stream::{self, Stream, StreamExt};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Result, Write};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::RwLock;
+use uuid::Uuid;
 
-// struct type to represent the grid of tiles
-#[derive(Debug)]
+// default board size and win length, as set by the Day 12 challenge
+const DEFAULT_SIZE: usize = 4;
+
+// largest board width or height accepted by the /12/reset endpoint
+const MAX_SIZE: usize = 20;
+
+// how long a game session may go without being played before it expires
+const GAME_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+// score of a won game for the AI opponent, far above anything the board heuristic can reach
+const WIN_SCORE: i32 = 1_000_000;
+
+// number of positions the AI opponent may visit for a single move, multiplied by the number of tiles on
+// the board, since every position visited costs time in proportion to them; about 300,000 positions on
+// the 4x4 board, 120,000 on a 7x6 one and 12,500 on the largest
+const AI_TILE_BUDGET: u64 = 5_000_000;
+
+// seed of the random generator behind the /12/random-board endpoint, as set by the Day 12 challenge
+pub const RANDOM_BOARD_SEED: u64 = 2024;
+
+// id under which the board of the /12/board endpoint is saved, alongside the game sessions
+pub const BOARD_ID: Uuid = Uuid::nil();
+
+// number of board changes kept for spectators of the /12/events stream, or of a game session's
+// events stream, who fall behind
+pub const BOARD_EVENT_CAPACITY: usize = 64;
+
+// marker replacing the tiles of the winning line in a highlighted board
+const WINNING_TILE: char = '⭐';
+
+// the vertical, horizontal and both diagonal directions a winning line can run in
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+// type alias to represent the optional bearer token identifying the player making a request
+type PlayerToken = Option<TypedHeader<Authorization<Bearer>>>;
+
+// struct type to represent the grid of tiles, stored column by column from the bottom up
+#[derive(Clone, Debug)]
 pub struct Game {
-    pub board: [[Tile; 4]; 4],
+    pub width: usize,
+    pub height: usize,
+    pub connect: usize,
+    pub board: Vec<Vec<Tile>>,
     pub status: Option<Outcome>,
+    pub turn: Tile,
+    pub seats: Option<Seats>,
+    pub moves: Vec<Move>,
+    pub winning_line: Vec<(usize, usize)>,
+}
+
+// struct type to represent the number of positions the AI opponent may still visit in its search
+#[derive(Debug)]
+struct SearchBudget {
+    nodes: u64,
+}
+
+// struct type to represent a single move, in the order it was played
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Move {
+    pub team: Tile,
+    pub column: usize,
+}
+
+// struct type to represent a single move in the /12/history endpoint, with columns numbered from 1 as
+// in the /12/place endpoint
+#[derive(Clone, Debug, Serialize)]
+pub struct MoveBody {
+    number: usize,
+    team: Tile,
+    column: usize,
+}
+
+// methods for the MoveBody type
+impl MoveBody {
+    // describe the move played with the given number, counting from 1
+    fn numbered(number: usize, m: &Move) -> Self {
+        Self {
+            number,
+            team: m.team,
+            column: m.column + 1,
+        }
+    }
+}
+
+// enum type to represent a change made to the board, as published to the /12/events stream
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum BoardChange {
+    Move(MoveBody),
+    Undo(MoveBody),
+    Reset,
+    Random,
+}
+
+// methods for the BoardChange type
+impl BoardChange {
+    // the name of the server-sent event announcing the change
+    fn event_name(&self) -> &'static str {
+        match self {
+            Self::Move(_) => "move",
+            Self::Undo(_) => "undo",
+            Self::Reset => "reset",
+            Self::Random => "random",
+        }
+    }
+}
+
+// struct type to represent a change made to the board, together with the board it left behind
+#[derive(Clone, Debug)]
+pub struct BoardEvent {
+    pub change: BoardChange,
+    pub board: String,
+}
+
+// struct type to represent the response body of the /12/history endpoint
+#[derive(Debug, Serialize)]
+pub struct HistoryBody {
+    moves: Vec<MoveBody>,
+}
+
+// struct type to represent a tile in the JSON board, with columns and rows numbered from 1 and rows
+// counted from the bottom
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CellBody {
+    column: usize,
+    row: usize,
+}
+
+// enum type to represent whether the game in the JSON board is still being played
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardStatus {
+    InProgress,
+    Won,
+    Draw,
+}
+
+// struct type to represent the board of a game when JSON is requested, with the tiles of each column
+// listed from the bottom up
+#[derive(Debug, Serialize)]
+pub struct BoardBody {
+    width: usize,
+    height: usize,
+    connect: usize,
+    columns: Vec<Vec<Tile>>,
+    status: BoardStatus,
+    winner: Option<Tile>,
+    next_player: Option<Tile>,
+    winning_cells: Vec<CellBody>,
+}
+
+// enum type to represent how a board is rendered in a response
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BoardFormat {
+    #[default]
+    Text,
+    HighlightedText,
+    Json,
+}
+
+// methods for the BoardFormat type
+impl BoardFormat {
+    // JSON if the Accept header lists application/json, otherwise the emoji grid, with the winning line
+    // marked if the query parameters ask for it
+    pub fn negotiate(headers: &HeaderMap, view: &ViewParameters) -> Self {
+        let wants_json = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|range| range.split(';').next())
+            .any(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"));
+
+        match (wants_json, view.highlight) {
+            (true, _) => Self::Json,
+            (false, true) => Self::HighlightedText,
+            (false, false) => Self::Text,
+        }
+    }
+}
+
+// struct type to represent the players seated at a strict game, by the token each one was issued
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Seats {
+    pub(crate) cookie: Option<String>,
+    pub(crate) milk: Option<String>,
+}
+
+// methods for the Seats type
+impl Seats {
+    // the seat for a team
+    fn seat(&self, tile: Tile) -> Option<&String> {
+        match tile {
+            Tile::Cookie => self.cookie.as_ref(),
+            Tile::Milk => self.milk.as_ref(),
+            Tile::Empty => None,
+        }
+    }
+
+    // seat a player for a team, returning their token, or None if the seat is already taken
+    pub fn join(&mut self, tile: Tile) -> Option<String> {
+        let seat = match tile {
+            Tile::Cookie => &mut self.cookie,
+            Tile::Milk => &mut self.milk,
+            Tile::Empty => return None,
+        };
+        if seat.is_some() {
+            return None;
+        }
+
+        let token = Uuid::new_v4().simple().to_string();
+        *seat = Some(token.clone());
+        Some(token)
+    }
+
+    // whether the token belongs to the player seated for a team
+    pub fn is_seated(&self, tile: Tile, token: &str) -> bool {
+        self.seat(tile).is_some_and(|t| t == token)
+    }
+
+    // whether the token belongs to any seated player
+    pub fn is_player(&self, token: &str) -> bool {
+        self.is_seated(Tile::Cookie, token) || self.is_seated(Tile::Milk, token)
+    }
+}
+
+// struct type to represent a single game session, with the time it was last played and the changes
+// published to its spectators
+#[derive(Debug)]
+struct GameSession {
+    game: Arc<RwLock<Game>>,
+    events: broadcast::Sender<BoardEvent>,
+    last_active: Instant,
+}
+
+// methods for the GameSession type
+impl GameSession {
+    fn new(game: Game) -> Self {
+        let (events, _) = broadcast::channel(BOARD_EVENT_CAPACITY);
+        Self {
+            game: Arc::new(RwLock::new(game)),
+            events,
+            last_active: Instant::now(),
+        }
+    }
+}
+
+// struct type to represent every game session, each one expiring once idle for too long
+#[derive(Debug)]
+pub struct GameRegistry {
+    sessions: HashMap<Uuid, GameSession>,
+    idle_timeout: Duration,
+}
+
+// methods for the GameRegistry type
+impl GameRegistry {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            sessions: HashMap::new(),
+            idle_timeout,
+        }
+    }
+
+    // forget every game session which has been idle for too long
+    pub fn evict_idle(&mut self) {
+        let idle_timeout = self.idle_timeout;
+        self.sessions
+            .retain(|_, session| session.last_active.elapsed() < idle_timeout);
+    }
+
+    // how long each game session has gone without being played or read
+    pub fn idle_times(&self) -> Vec<(Uuid, Duration)> {
+        self.sessions
+            .iter()
+            .map(|(id, session)| (*id, session.last_active.elapsed()))
+            .collect()
+    }
+
+    // the time a game session may go without being played before it expires
+    pub fn idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    // add back a game session under the id it had before a restart
+    pub fn restore(&mut self, id: Uuid, game: Game) {
+        self.sessions.insert(id, GameSession::new(game));
+    }
+
+    // add a new game session, returning its id
+    pub fn create(&mut self, game: Game) -> Uuid {
+        self.evict_idle();
+
+        let id = Uuid::new_v4();
+        self.sessions.insert(id, GameSession::new(game));
+        id
+    }
+
+    // get a game session which has not expired, marking it as active
+    pub fn get(&mut self, id: Uuid) -> Option<Arc<RwLock<Game>>> {
+        self.session(id).map(|(game, _)| game)
+    }
+
+    // get a game session which has not expired along with the sender of its events, marking it as active
+    pub fn session(
+        &mut self,
+        id: Uuid,
+    ) -> Option<(Arc<RwLock<Game>>, broadcast::Sender<BoardEvent>)> {
+        self.evict_idle();
+
+        let session = self.sessions.get_mut(&id)?;
+        session.last_active = Instant::now();
+        Some((session.game.clone(), session.events.clone()))
+    }
+
+    // end a game session, returning whether it existed
+    pub fn remove(&mut self, id: Uuid) -> bool {
+        self.evict_idle();
+        self.sessions.remove(&id).is_some()
+    }
+}
+
+// implement the Default trait for the GameRegistry type
+impl Default for GameRegistry {
+    fn default() -> Self {
+        Self::new(GAME_IDLE_TIMEOUT)
+    }
+}
+
+// struct type to represent the query parameters for the /12/games endpoint
+#[derive(Debug, Deserialize)]
+pub struct CreateGameParameters {
+    width: Option<usize>,
+    height: Option<usize>,
+    connect: Option<usize>,
+    #[serde(default)]
+    strict: bool,
+}
+
+// struct type to represent the response body of the /12/games endpoint
+#[derive(Debug, Serialize)]
+pub struct GameCreatedBody {
+    id: Uuid,
+    width: usize,
+    height: usize,
+    connect: usize,
+    strict: bool,
+}
+
+// struct type to represent the response body of the /12/games/{id}/join/{team} endpoint
+#[derive(Debug, Serialize)]
+pub struct SeatBody {
+    team: String,
+    token: String,
+}
+
+// enum type to represent how far ahead the AI opponent searches
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+// methods for the Difficulty type
+impl Difficulty {
+    // the number of moves, counting both teams, the AI opponent looks ahead
+    pub fn depth(self) -> usize {
+        match self {
+            Self::Easy => 1,
+            Self::Medium => 4,
+            Self::Hard => 7,
+        }
+    }
+}
+
+// struct type to represent the column the AI opponent chose for a team, along with the game it chose it
+// for
+#[derive(Debug)]
+struct AiChoice {
+    game: Game,
+    tile: Tile,
+    column: Option<usize>,
+}
+
+// struct type to represent the query parameters for the /12/ai/{team} endpoint
+#[derive(Debug, Deserialize)]
+pub struct AiParameters {
+    #[serde(default)]
+    difficulty: Difficulty,
+}
+
+// struct type to represent the query parameters shared by the endpoints rendering a board
+#[derive(Debug, Default, Deserialize)]
+pub struct ViewParameters {
+    #[serde(default)]
+    highlight: bool,
+}
+
+// struct type to represent the query parameters for the /12/random-board endpoint
+#[derive(Debug, Deserialize)]
+pub struct RandomBoardParameters {
+    seed: Option<u64>,
+}
+
+// struct type to represent the query parameters for the /12/reset endpoint
+#[derive(Debug, Deserialize)]
+pub struct ResetParameters {
+    width: Option<usize>,
+    height: Option<usize>,
+    connect: Option<usize>,
 }
 
 // enum type to represent the outcome of a game
@@ -26,6 +447,18 @@ pub enum Outcome {
     Neither,
 }
 
+// methods for the Outcome type
+impl Outcome {
+    // the outcome of a game won by the given team
+    fn won_by(tile: Tile) -> Self {
+        match tile {
+            Tile::Cookie => Self::Cookie,
+            Tile::Milk => Self::Milk,
+            Tile::Empty => Self::Neither,
+        }
+    }
+}
+
 // implement the Display trait for the Outcome type
 impl Display for Outcome {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
@@ -42,10 +475,39 @@ impl Display for Outcome {
 impl Game {
     // create a new game board
     pub fn new() -> Self {
-        let board = [[Tile::Empty; 4]; 4];
-        let status = None;
+        Self {
+            width: DEFAULT_SIZE,
+            height: DEFAULT_SIZE,
+            connect: DEFAULT_SIZE,
+            board: vec![vec![Tile::Empty; DEFAULT_SIZE]; DEFAULT_SIZE],
+            status: None,
+            turn: Tile::Cookie,
+            seats: None,
+            moves: Vec::new(),
+            winning_line: Vec::new(),
+        }
+    }
 
-        Self { board, status }
+    // create a new game board of the given size, where connecting the given number of tiles wins;
+    // returns None if the board is empty, too large, or the win length does not fit on it
+    pub fn with_size(width: usize, height: usize, connect: usize) -> Option<Self> {
+        let valid_size = (1..=MAX_SIZE).contains(&width) && (1..=MAX_SIZE).contains(&height);
+        let valid_connect = (1..=width.max(height)).contains(&connect);
+        if !valid_size || !valid_connect {
+            return None;
+        }
+
+        Some(Self {
+            width,
+            height,
+            connect,
+            board: vec![vec![Tile::Empty; height]; width],
+            status: None,
+            turn: Tile::Cookie,
+            seats: None,
+            moves: Vec::new(),
+            winning_line: Vec::new(),
+        })
     }
 
     // get the contents of any arbitrary tile in the game board, given it's x and y coordinates
@@ -53,36 +515,60 @@ impl Game {
         self.board[x][y]
     }
 
+    // count the tiles matching the given tile in a straight line from, but not including, x and y
+    fn count_in_direction(&self, x: usize, y: usize, dx: isize, dy: isize, tile: Tile) -> usize {
+        let mut count = 0;
+        let (mut x, mut y) = (x as isize, y as isize);
+
+        loop {
+            x += dx;
+            y += dy;
+            let on_board =
+                (0..self.width as isize).contains(&x) && (0..self.height as isize).contains(&y);
+            if !on_board || self.get_tile(x as usize, y as usize) != tile {
+                return count;
+            }
+            count += 1;
+        }
+    }
+
     // test if there is a game winner
     pub fn check_winner(&self, x: usize, y: usize) -> bool {
+        self.find_winning_line(x, y).is_some()
+    }
+
+    // find a line through the tile at x and y that reaches the win length, returning the coordinates of
+    // its tiles from one end to the other
+    fn find_winning_line(&self, x: usize, y: usize) -> Option<Vec<(usize, usize)>> {
         // the current tile coordinates
         let tile = self.get_tile(x, y);
+        if tile == Tile::Empty {
+            return None;
+        }
 
-        // define the possible winning combinations as an array of tuples
-        let top_to_bottom = [(x, 0), (x, 1), (x, 2), (x, 3)];
-        let left_to_right = [(0, y), (1, y), (2, y), (3, y)];
-        let left_diagonal = [(0, 3), (1, 2), (2, 1), (3, 0)];
-        let right_diagonal = [(0, 0), (1, 1), (2, 2), (3, 3)];
-
-        // define a closure which, when given the current position, check the board against the above defined winning combinations
-        let t_check = |pos: &[(usize, usize)]| -> bool {
-            pos.contains(&(x, y))
-                && pos
-                    .iter()
-                    .map(|(x, y)| self.get_tile(*x, *y))
-                    .all(|t| t == tile)
-        };
+        // a line wins if the tile extends it, in either direction, to the win length
+        DIRECTIONS.iter().find_map(|&(dx, dy)| {
+            let behind = self.count_in_direction(x, y, -dx, -dy, tile) as isize;
+            let ahead = self.count_in_direction(x, y, dx, dy, tile) as isize;
+            if ((1 + behind + ahead) as usize) < self.connect {
+                return None;
+            }
 
-        // call the closure with each of the winning combinations to check if any are true
-        t_check(&top_to_bottom)
-            || t_check(&left_to_right)
-            || t_check(&left_diagonal)
-            || t_check(&right_diagonal)
+            let line = (-behind..=ahead)
+                .map(|i| {
+                    (
+                        (x as isize + i * dx) as usize,
+                        (y as isize + i * dy) as usize,
+                    )
+                })
+                .collect();
+            Some(line)
+        })
     }
 
     // make a move by placing an item
     pub fn make_move(&mut self, tile: Tile, column: usize) -> bool {
-        if self.status.is_some() {
+        if self.status.is_some() || column >= self.width {
             return false;
         }
 
@@ -95,6 +581,8 @@ impl Game {
             None => return false,
         };
         *find_tile = tile;
+        self.turn = tile.opponent();
+        self.moves.push(Move { team: tile, column });
 
         let is_full = !self.board.iter().flatten().any(|t| *t == Tile::Empty);
 
@@ -102,16 +590,276 @@ impl Game {
             self.status = Some(Outcome::Neither);
         }
 
-        if self.check_winner(column, y) {
-            self.status = Some(match tile {
-                Tile::Cookie => Outcome::Cookie,
-                Tile::Milk => Outcome::Milk,
-                Tile::Empty => unreachable!(),
-            });
+        if let Some(line) = self.find_winning_line(column, y) {
+            self.winning_line = line;
+            self.status = Some(Outcome::won_by(tile));
         }
 
         true
     }
+
+    // take back the last move, clearing any outcome, and return it; the team that made it moves next
+    pub fn undo(&mut self) -> Option<Move> {
+        let last = self.moves.pop()?;
+
+        if let Some(top) = self.board[last.column]
+            .iter_mut()
+            .rev()
+            .find(|t| **t != Tile::Empty)
+        {
+            *top = Tile::Empty;
+        }
+        self.status = None;
+        self.winning_line.clear();
+        self.turn = last.team;
+
+        Some(last)
+    }
+
+    // rebuild the game as it was after the first n moves, or None if fewer moves have been played
+    pub fn replay(&self, n: usize) -> Option<Game> {
+        let moves = self.moves.get(..n)?;
+        Game::from_moves(self.width, self.height, self.connect, moves)
+    }
+
+    // build a game of the given size by playing the given moves, or None if any of them is not allowed
+    pub fn from_moves(width: usize, height: usize, connect: usize, moves: &[Move]) -> Option<Game> {
+        let mut game = Game::with_size(width, height, connect)?;
+
+        for m in moves {
+            if m.team == Tile::Empty || !game.make_move(m.team, m.column) {
+                return None;
+            }
+        }
+
+        Some(game)
+    }
+
+    // build a game of the given size from a board filled without moves, such as a random one, with the
+    // tiles listed column by column from the bottom up; returns None if they do not fill the board
+    pub fn from_tiles(width: usize, height: usize, connect: usize, tiles: &[Tile]) -> Option<Game> {
+        let mut game = Game::with_size(width, height, connect)?;
+        if tiles.len() != width * height {
+            return None;
+        }
+
+        game.board = tiles.chunks(height).map(<[Tile]>::to_vec).collect();
+        game.settle();
+        Some(game)
+    }
+
+    // build a game of the given size filled with random tiles, drawn row by row from the top left as in
+    // the Day 12 challenge
+    pub fn random(width: usize, height: usize, connect: usize, rng: &mut impl Rng) -> Option<Game> {
+        let mut board = vec![vec![Tile::Empty; height]; width];
+        for y in (0..height).rev() {
+            for column in board.iter_mut() {
+                column[y] = match rng.gen::<bool>() {
+                    true => Tile::Cookie,
+                    false => Tile::Milk,
+                };
+            }
+        }
+
+        Game::from_tiles(width, height, connect, &board.concat())
+    }
+
+    // settle the outcome of a board filled without moves; when both teams have a line, the first one
+    // found reading from the top left wins
+    fn settle(&mut self) {
+        let line = (0..self.height)
+            .rev()
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .find_map(|(x, y)| self.find_winning_line(x, y));
+        let is_full = !self.board.iter().flatten().any(|t| *t == Tile::Empty);
+
+        match line {
+            Some(line) => {
+                let (x, y) = line[0];
+                self.status = Some(Outcome::won_by(self.get_tile(x, y)));
+                self.winning_line = line;
+            }
+            None if is_full => self.status = Some(Outcome::Neither),
+            None => self.status = None,
+        }
+    }
+
+    // the columns of the board, from the centre outwards, which is where the strongest moves usually are
+    fn columns_by_preference(&self) -> Vec<usize> {
+        let mut columns: Vec<usize> = (0..self.width).collect();
+        columns.sort_by_key(|c| (2 * *c as isize - (self.width as isize - 1)).abs());
+        columns
+    }
+
+    // score the board for a team by every line of win length still open to one team only, favouring
+    // lines closer to completion
+    fn evaluate(&self, team: Tile) -> i32 {
+        let connect = self.connect as isize;
+        let mut score = 0;
+
+        for x in 0..self.width as isize {
+            for y in 0..self.height as isize {
+                for (dx, dy) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                    let (end_x, end_y) = (x + dx * (connect - 1), y + dy * (connect - 1));
+                    let fits = (0..self.width as isize).contains(&end_x)
+                        && (0..self.height as isize).contains(&end_y);
+                    if !fits {
+                        continue;
+                    }
+
+                    let (mut ours, mut theirs) = (0, 0);
+                    for i in 0..connect {
+                        match self.get_tile((x + dx * i) as usize, (y + dy * i) as usize) {
+                            Tile::Empty => {}
+                            tile if tile == team => ours += 1,
+                            _ => theirs += 1,
+                        }
+                    }
+
+                    if theirs == 0 {
+                        score += ours * ours;
+                    } else if ours == 0 {
+                        score -= theirs * theirs;
+                    }
+                }
+            }
+        }
+
+        score
+    }
+
+    // score the game for a team with a minimax search, pruning branches with alpha-beta bounds; wins
+    // found sooner score higher. Moves are made and taken back on the game itself, and None is returned
+    // once the budget runs out
+    fn minimax(
+        &mut self,
+        team: Tile,
+        to_move: Tile,
+        depth: usize,
+        mut alpha: i32,
+        mut beta: i32,
+        budget: &mut SearchBudget,
+    ) -> Option<i32> {
+        if !budget.spend() {
+            return None;
+        }
+
+        match self.status {
+            Some(Outcome::Neither) => return Some(0),
+            Some(outcome) => {
+                let score = WIN_SCORE + depth as i32;
+                let won = matches!(
+                    (outcome, team),
+                    (Outcome::Cookie, Tile::Cookie) | (Outcome::Milk, Tile::Milk)
+                );
+                return Some(if won { score } else { -score });
+            }
+            None => {}
+        }
+
+        if depth == 0 {
+            return Some(self.evaluate(team));
+        }
+
+        let maximizing = to_move == team;
+        let mut best = if maximizing { i32::MIN } else { i32::MAX };
+
+        for column in self.columns_by_preference() {
+            if !self.make_move(to_move, column) {
+                continue;
+            }
+            let score = self.minimax(team, to_move.opponent(), depth - 1, alpha, beta, budget);
+            self.undo();
+            let score = score?;
+
+            if maximizing {
+                best = best.max(score);
+                alpha = alpha.max(best);
+            } else {
+                best = best.min(score);
+                beta = beta.min(best);
+            }
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        Some(best)
+    }
+
+    // find the best column for a team to play, looking the given number of moves ahead, or None if the
+    // budget runs out first
+    fn search(&mut self, team: Tile, depth: usize, budget: &mut SearchBudget) -> Option<usize> {
+        let mut best: Option<(usize, i32)> = None;
+        let mut alpha = i32::MIN;
+
+        for column in self.columns_by_preference() {
+            if !self.make_move(team, column) {
+                continue;
+            }
+            let score = self.minimax(team, team.opponent(), depth - 1, alpha, i32::MAX, budget);
+            self.undo();
+            let score = score?;
+
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((column, score));
+                alpha = alpha.max(score);
+            }
+        }
+
+        best.map(|(column, _)| column)
+    }
+
+    // find the best column for a team to play, looking up to the given number of moves ahead; returns
+    // None if the game is over. The search looks one move further ahead at a time, until it reaches the
+    // depth or runs out of budget, and the deepest search it completed decides
+    pub fn best_move(&self, team: Tile, depth: usize) -> Option<usize> {
+        if self.status.is_some() {
+            return None;
+        }
+
+        let mut root = self.clone();
+        root.seats = None;
+        let mut budget = SearchBudget::for_board(self.width, self.height);
+
+        // should even the shallowest search run out of budget, the most central open column is played
+        let mut best = root
+            .columns_by_preference()
+            .into_iter()
+            .find(|&column| root.board[column].contains(&Tile::Empty));
+        for depth in 1..=depth.max(1) {
+            match root.search(team, depth, &mut budget) {
+                Some(column) => best = Some(column),
+                None => break,
+            }
+        }
+
+        best
+    }
+}
+
+// methods for the SearchBudget type
+impl SearchBudget {
+    // the budget for a search of a board of the given size, where every position visited costs time in
+    // proportion to the number of tiles
+    fn for_board(width: usize, height: usize) -> Self {
+        let tiles = (width * height).max(1) as u64;
+        Self {
+            nodes: AI_TILE_BUDGET / tiles,
+        }
+    }
+
+    // spend the budget on one more position, returning false if there was none left
+    fn spend(&mut self) -> bool {
+        match self.nodes.checked_sub(1) {
+            Some(nodes) => {
+                self.nodes = nodes;
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 // implement the Default trait for the Game type
@@ -125,17 +873,25 @@ impl Default for Game {
 impl Display for Game {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         let wall = '⬜';
-        for y in (0..4).rev() {
+
+        // the alternate form, {:#}, marks the tiles of the winning line
+        let highlight = f.alternate();
+
+        for y in (0..self.height).rev() {
             f.write_char(wall)?;
-            for x in 0..4 {
-                f.write_fmt(format_args!("{}", self.get_tile(x, y)))?
+            for x in 0..self.width {
+                if highlight && self.winning_line.contains(&(x, y)) {
+                    f.write_char(WINNING_TILE)?;
+                } else {
+                    f.write_fmt(format_args!("{}", self.get_tile(x, y)))?
+                }
             }
             f.write_fmt(format_args!("{}\n", wall))?
         }
-        f.write_fmt(format_args!(
-            "{}{}{}{}{}{}\n",
-            wall, wall, wall, wall, wall, wall
-        ))?;
+        for _ in 0..self.width + 2 {
+            f.write_char(wall)?;
+        }
+        f.write_char('\n')?;
 
         if let Some(outcome) = self.status {
             f.write_fmt(format_args!("{}\n", outcome))?;
@@ -145,14 +901,67 @@ impl Display for Game {
     }
 }
 
+// implement the From trait to build the JSON board of a game
+impl From<&Game> for BoardBody {
+    fn from(game: &Game) -> Self {
+        let (status, winner) = match game.status {
+            None => (BoardStatus::InProgress, None),
+            Some(Outcome::Cookie) => (BoardStatus::Won, Some(Tile::Cookie)),
+            Some(Outcome::Milk) => (BoardStatus::Won, Some(Tile::Milk)),
+            Some(Outcome::Neither) => (BoardStatus::Draw, None),
+        };
+        let winning_cells = game
+            .winning_line
+            .iter()
+            .map(|&(x, y)| CellBody {
+                column: x + 1,
+                row: y + 1,
+            })
+            .collect();
+
+        Self {
+            width: game.width,
+            height: game.height,
+            connect: game.connect,
+            columns: game.board.clone(),
+            status,
+            winner,
+            next_player: game.status.is_none().then_some(game.turn),
+            winning_cells,
+        }
+    }
+}
+
 // enum type to represent the contents of a tile
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Tile {
     Empty,
     Cookie,
     Milk,
 }
 
+// methods for the Tile type
+impl Tile {
+    // the name of the tile, as used in the /12/place endpoint
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::Cookie => "cookie",
+            Self::Milk => "milk",
+        }
+    }
+
+    // the team playing against this one
+    pub fn opponent(self) -> Self {
+        match self {
+            Self::Cookie => Self::Milk,
+            Self::Milk => Self::Cookie,
+            Self::Empty => Self::Empty,
+        }
+    }
+}
+
 // implement the Display trait for the Tile type
 impl Display for Tile {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
@@ -179,50 +988,747 @@ impl FromStr for Tile {
     }
 }
 
-// Day 12 Task 1 Handler - current board state, gets the current state of the game board
-#[debug_handler]
-#[tracing::instrument(name = "Day 12 Task 1 Handler - Board State", skip(state))]
-pub async fn day_12_get_board_state(State(state): State<AppState>) -> impl IntoResponse {
-    let game = state.game.read().await;
-    let response_body = format!("{}", game);
-    (StatusCode::OK, response_body).into_response()
+// function to render the board of a game as the response body, in the requested format
+fn render_board(game: &Game, status: StatusCode, format: BoardFormat) -> Response {
+    match format {
+        BoardFormat::Text => (status, format!("{}", game)).into_response(),
+        BoardFormat::HighlightedText => (status, format!("{:#}", game)).into_response(),
+        BoardFormat::Json => (status, Json(BoardBody::from(game))).into_response(),
+    }
 }
 
-// Day 12 Task 1 Handler - reset the board to an empty state and return it
-#[debug_handler]
-#[tracing::instrument(name = "Day 12 Task 1 Handler - Reset Board", skip(state))]
-pub async fn day12_post_reset_board(State(state): State<AppState>) -> impl IntoResponse {
-    let mut game = state.game.write().await;
-    *game = Game::default();
-    let response_body = format!("{}", game);
-    (StatusCode::OK, response_body).into_response()
+// function to render the board of a game as a successful response
+fn board_response(game: &Game, format: BoardFormat) -> Response {
+    render_board(game, StatusCode::OK, format)
 }
 
-// Day 12, Task 2 Handler - place an item into the board
-#[debug_handler]
-#[tracing::instrument(name = "Day 12 Task 2 Handler - Place an Item", skip(state))]
-pub async fn day12_post_place_item(
-    State(state): State<AppState>,
-    Path(path): Path<(String, String)>,
-) -> impl IntoResponse {
-    let tile = match Tile::from_str(&path.0) {
-        Ok(tile) => tile,
-        Err(_) => return (StatusCode::BAD_REQUEST).into_response(),
+// function to build an empty game of the size given in the query parameters, or the reason for rejecting them
+fn sized_game(
+    width: Option<usize>,
+    height: Option<usize>,
+    connect: Option<usize>,
+) -> std::result::Result<Game, String> {
+    let width = width.unwrap_or(DEFAULT_SIZE);
+    let height = height.unwrap_or(DEFAULT_SIZE);
+    let connect = connect.unwrap_or(DEFAULT_SIZE);
+
+    Game::with_size(width, height, connect).ok_or_else(|| {
+        format!(
+            "Boards must be 1 to {} tiles wide and high, with a win length that fits on the board.",
+            MAX_SIZE
+        )
+    })
+}
+
+// function to replace a game with an empty board of the requested size, and render it; the players
+// seated at a strict game keep their seats
+fn reset_response(game: &mut Game, params: &ResetParameters, format: BoardFormat) -> Response {
+    match sized_game(params.width, params.height, params.connect) {
+        Ok(mut new_game) => {
+            new_game.seats = game.seats.take();
+            *game = new_game;
+            board_response(game, format)
+        }
+        Err(response_body) => (StatusCode::BAD_REQUEST, response_body).into_response(),
+    }
+}
+
+// function to place an item for a team into a column of a game, given as path parameters, and render the board
+fn place_response(game: &mut Game, team: &str, column: &str, format: BoardFormat) -> Response {
+    let tile = match Tile::from_str(team) {
+        Ok(tile) => tile,
+        Err(_) => return (StatusCode::BAD_REQUEST).into_response(),
     };
 
-    let column: usize = match path.1.parse::<usize>() {
-        Ok(num) if (1..=4).contains(&num) => num,
+    let column: usize = match column.parse::<usize>() {
+        Ok(num) if (1..=game.width).contains(&num) => num,
         _ => return (StatusCode::BAD_REQUEST).into_response(),
     };
 
-    let mut game = state.game.write().await;
-
     if game.make_move(tile, column - 1) {
-        let response_body = format!("{}", game);
-        return (StatusCode::OK, response_body).into_response();
+        render_board(game, StatusCode::OK, format)
     } else {
-        let response_body = format!("{}", game);
-        return (StatusCode::SERVICE_UNAVAILABLE, response_body).into_response();
+        render_board(game, StatusCode::SERVICE_UNAVAILABLE, format)
+    }
+}
+
+// function to check a move against the rules of a strict game, returning the reason it is refused;
+// loose games accept any move
+fn strict_violation(game: &Game, team: &str, token: Option<&str>) -> Option<(StatusCode, String)> {
+    let seats = game.seats.as_ref()?;
+    let tile = Tile::from_str(team).ok()?;
+
+    if !token.is_some_and(|token| seats.is_seated(tile, token)) {
+        return Some((
+            StatusCode::FORBIDDEN,
+            format!("Only the player seated for {} may place its items.", team),
+        ));
+    }
+    if game.status.is_none() && game.turn != tile {
+        return Some((StatusCode::CONFLICT, format!("It is not {}'s turn.", team)));
+    }
+
+    None
+}
+
+// function to let the AI opponent choose a column for a team in a copy of a game; the search runs on a
+// blocking thread, so neither the game's lock nor an async worker is held while the AI thinks
+async fn ai_choice(
+    game: Game,
+    team: &str,
+    difficulty: Difficulty,
+) -> std::result::Result<AiChoice, Response> {
+    let tile = Tile::from_str(team).map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+
+    let depth = difficulty.depth();
+    let (game, column) = tokio::task::spawn_blocking(move || {
+        let column = game.best_move(tile, depth);
+        (game, column)
+    })
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(AiChoice { game, tile, column })
+}
+
+// function to place the item the AI opponent chose, and render the board; the column it chose is
+// returned in the x-ai-column header
+fn ai_response(game: &mut Game, choice: &AiChoice, format: BoardFormat) -> Response {
+    // the choice only holds for the board the AI opponent thought about
+    if game.board != choice.game.board || game.moves.len() != choice.game.moves.len() {
+        let response_body = "The board changed while the AI opponent was thinking.".to_string();
+        return (StatusCode::CONFLICT, response_body).into_response();
+    }
+
+    match choice.column {
+        Some(column) if game.make_move(choice.tile, column) => {
+            let column = (column + 1).to_string();
+            (
+                [("x-ai-column", column)],
+                render_board(game, StatusCode::OK, format),
+            )
+                .into_response()
+        }
+        _ => render_board(game, StatusCode::SERVICE_UNAVAILABLE, format),
+    }
+}
+
+// function to list the moves of a game as JSON
+fn history_response(game: &Game) -> Response {
+    let moves = game
+        .moves
+        .iter()
+        .enumerate()
+        .map(|(i, m)| MoveBody::numbered(i + 1, m))
+        .collect();
+
+    (StatusCode::OK, Json(HistoryBody { moves })).into_response()
+}
+
+// function to take back the last move of a game, and render the board
+fn undo_response(game: &mut Game, format: BoardFormat) -> Response {
+    match game.undo() {
+        Some(_) => board_response(game, format),
+        None => {
+            let response_body = "There are no moves to undo.".to_string();
+            (StatusCode::CONFLICT, response_body).into_response()
+        }
+    }
+}
+
+// function to render the board of a game as it was after the first n moves
+fn replay_response(game: &Game, n: usize, format: BoardFormat) -> Response {
+    match game.replay(n) {
+        Some(replayed) => board_response(&replayed, format),
+        None => {
+            let response_body = format!(
+                "Only {} moves have been played in this game.",
+                game.moves.len()
+            );
+            (StatusCode::NOT_FOUND, response_body).into_response()
+        }
+    }
+}
+
+// function to publish a change made to a board to its events stream; the event is dropped when nobody
+// is watching
+fn publish(events: &broadcast::Sender<BoardEvent>, game: &Game, change: BoardChange) {
+    let board = format!("{}", game);
+    let _ = events.send(BoardEvent { change, board });
+}
+
+// function to publish the move that was just played on a board to its events stream
+fn publish_last_move(events: &broadcast::Sender<BoardEvent>, game: &Game) {
+    if let Some(last) = game.moves.last() {
+        let change = BoardChange::Move(MoveBody::numbered(game.moves.len(), last));
+        publish(events, game, change);
+    }
+}
+
+// function to make a change to a game, saving the changed game before it replaces the one in memory, so
+// that a failed save leaves the game as it was; requests which change nothing are not saved
+async fn save_change(
+    state: &AppState,
+    id: Uuid,
+    game: &mut Game,
+    change: impl FnOnce(&mut Game) -> Response,
+) -> Response {
+    let mut changed = game.clone();
+    let response = change(&mut changed);
+    if !response.status().is_success() {
+        return response;
+    }
+
+    match state.saved_games.save(id, &changed).await {
+        Ok(()) => {
+            *game = changed;
+            response
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+// function to build the response for a game id that is unknown or has expired
+fn game_not_found(id: Uuid) -> Response {
+    let response_body = format!("No game with id {}.", id);
+    (StatusCode::NOT_FOUND, response_body).into_response()
+}
+
+// Day 12 Task 1 Handler - current board state, gets the current state of the game board
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Task 1 Handler - Board State", skip(state, headers))]
+pub async fn day_12_get_board_state(
+    State(state): State<AppState>,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let game = state.game.read().await;
+    board_response(&game, BoardFormat::negotiate(&headers, &view))
+}
+
+// Day 12 Task 1 Handler - reset the board to an empty state and return it, optionally resizing it
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Task 1 Handler - Reset Board", skip(state, headers))]
+pub async fn day12_post_reset_board(
+    State(state): State<AppState>,
+    Query(params): Query<ResetParameters>,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let mut game = state.game.write().await;
+    let format = BoardFormat::negotiate(&headers, &view);
+    let response = save_change(&state, BOARD_ID, &mut game, |game| {
+        reset_response(game, &params, format)
+    })
+    .await;
+
+    if response.status().is_success() {
+        *state.board_rng.write().await = StdRng::seed_from_u64(RANDOM_BOARD_SEED);
+        publish(&state.game_events, &game, BoardChange::Reset);
+    }
+    response
+}
+
+// Day 12, Task 3 Handler - fill the board with random tiles, drawn from the server's random generator,
+// which starts over on /12/reset, or from one seeded by the seed query parameter
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Task 3 Handler - Random Board", skip(state, headers))]
+pub async fn day12_get_random_board(
+    State(state): State<AppState>,
+    Query(params): Query<RandomBoardParameters>,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let mut game = state.game.write().await;
+    let format = BoardFormat::negotiate(&headers, &view);
+
+    // the server's generator only moves on once the board it drew has been saved
+    let mut rng = match params.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => state.board_rng.read().await.clone(),
+    };
+    let response = save_change(&state, BOARD_ID, &mut game, |game| {
+        match Game::random(game.width, game.height, game.connect, &mut rng) {
+            Some(random) => *game = random,
+            None => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+        board_response(game, format)
+    })
+    .await;
+
+    if response.status().is_success() {
+        if params.seed.is_none() {
+            *state.board_rng.write().await = rng;
+        }
+        publish(&state.game_events, &game, BoardChange::Random);
+    }
+    response
+}
+
+// Day 12, Task 2 Handler - place an item into the board
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Task 2 Handler - Place an Item", skip(state, headers))]
+pub async fn day12_post_place_item(
+    State(state): State<AppState>,
+    Path(path): Path<(String, String)>,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let mut game = state.game.write().await;
+    let format = BoardFormat::negotiate(&headers, &view);
+    let response = save_change(&state, BOARD_ID, &mut game, |game| {
+        place_response(game, &path.0, &path.1, format)
+    })
+    .await;
+
+    if response.status().is_success() {
+        publish_last_move(&state.game_events, &game);
+    }
+    response
+}
+
+// Day 12 Handler - let the AI opponent place an item for a team into the board
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - AI Move", skip(state, headers))]
+pub async fn day12_post_ai_move(
+    State(state): State<AppState>,
+    Path(team): Path<String>,
+    Query(params): Query<AiParameters>,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let game = state.game.read().await.clone();
+    let choice = match ai_choice(game, &team, params.difficulty).await {
+        Ok(choice) => choice,
+        Err(response) => return response,
+    };
+
+    let mut game = state.game.write().await;
+    let format = BoardFormat::negotiate(&headers, &view);
+    let response = save_change(&state, BOARD_ID, &mut game, |game| {
+        ai_response(game, &choice, format)
+    })
+    .await;
+
+    if response.status().is_success() {
+        publish_last_move(&state.game_events, &game);
+    }
+    response
+}
+
+// Day 12 Handler - take back the last move on the board
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - Undo", skip(state, headers))]
+pub async fn day12_post_undo(
+    State(state): State<AppState>,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let mut game = state.game.write().await;
+    let number = game.moves.len();
+    let last = game.moves.last().copied();
+    let format = BoardFormat::negotiate(&headers, &view);
+    let response = save_change(&state, BOARD_ID, &mut game, |game| {
+        undo_response(game, format)
+    })
+    .await;
+
+    if let (true, Some(last)) = (response.status().is_success(), last) {
+        let change = BoardChange::Undo(MoveBody::numbered(number, &last));
+        publish(&state.game_events, &game, change);
+    }
+    response
+}
+
+// Day 12 Handler - list the moves played on the board
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - History", skip(state))]
+pub async fn day12_get_history(State(state): State<AppState>) -> impl IntoResponse {
+    let game = state.game.read().await;
+    history_response(&game)
+}
+
+// Day 12 Handler - the board as it was after the given number of moves
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - Replay", skip(state, headers))]
+pub async fn day12_get_replay(
+    State(state): State<AppState>,
+    Path(n): Path<usize>,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let game = state.game.read().await;
+    replay_response(&game, n, BoardFormat::negotiate(&headers, &view))
+}
+
+// function to stream a board as server-sent events, starting with the given current state and followed by
+// every change received after it
+fn board_events(
+    receiver: broadcast::Receiver<BoardEvent>,
+    board: String,
+) -> Sse<impl Stream<Item = std::result::Result<Event, axum::Error>>> {
+    let current = stream::once(async move { Ok(Event::default().event("board").data(board)) });
+
+    let changes = stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                // a spectator that falls behind skips the changes it missed; the next board catches it up
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+    .flat_map(|event| {
+        let change = Event::default()
+            .event(event.change.event_name())
+            .json_data(&event.change);
+        let board = Ok(Event::default().event("board").data(event.board));
+        stream::iter([change, board])
+    });
+
+    Sse::new(current.chain(changes)).keep_alive(KeepAlive::default())
+}
+
+// Day 12 Handler - stream the board as server-sent events, starting with its current state and followed by
+// every move, undo and reset made to it
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - Board Events", skip(state))]
+pub async fn day12_get_events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = std::result::Result<Event, axum::Error>>> {
+    // subscribe before reading the board, so no change made in between is missed
+    let receiver = state.game_events.subscribe();
+    let board = format!("{}", state.game.read().await);
+    board_events(receiver, board)
+}
+
+// Day 12 Handler - create a new game session with its own board, optionally sized like /12/reset
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - Create Game", skip(state))]
+pub async fn day12_post_create_game(
+    State(state): State<AppState>,
+    Query(params): Query<CreateGameParameters>,
+) -> impl IntoResponse {
+    let mut game = match sized_game(params.width, params.height, params.connect) {
+        Ok(game) => game,
+        Err(response_body) => return (StatusCode::BAD_REQUEST, response_body).into_response(),
+    };
+
+    // a strict game enforces turns, and only accepts moves from the players seated for each team
+    if params.strict {
+        game.seats = Some(Seats::default());
+    }
+
+    let (width, height, connect) = (game.width, game.height, game.connect);
+    let id = state.games.write().await.create(game.clone());
+    if let Err(e) = state.saved_games.save(id, &game).await {
+        state.games.write().await.remove(id);
+        return e.into_response();
+    }
+
+    let response_body = GameCreatedBody {
+        id,
+        width,
+        height,
+        connect,
+        strict: params.strict,
+    };
+
+    (
+        StatusCode::CREATED,
+        [(header::LOCATION, format!("/12/games/{}", id))],
+        Json(response_body),
+    )
+        .into_response()
+}
+
+// Day 12 Handler - current board state of a game session
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - Game Board State", skip(state, headers))]
+pub async fn day12_get_game_board_state(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let game = match state.games.write().await.get(id) {
+        Some(game) => game,
+        None => return game_not_found(id),
+    };
+
+    let game = game.read().await;
+    board_response(&game, BoardFormat::negotiate(&headers, &view))
+}
+
+// Day 12 Handler - reset the board of a game session, optionally resizing it
+#[debug_handler]
+#[tracing::instrument(
+    name = "Day 12 Handler - Reset Game Board",
+    skip(state, headers, bearer)
+)]
+pub async fn day12_post_reset_game_board(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ResetParameters>,
+    bearer: PlayerToken,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let (game, events) = match state.games.write().await.session(id) {
+        Some(session) => session,
+        None => return game_not_found(id),
+    };
+
+    let mut game = game.write().await;
+
+    // only a seated player may reset a strict game
+    let token = bearer.as_ref().map(|TypedHeader(auth)| auth.token());
+    if let Some(seats) = &game.seats {
+        if !token.is_some_and(|token| seats.is_player(token)) {
+            let response_body = "Only a seated player may reset this game.".to_string();
+            return (StatusCode::FORBIDDEN, response_body).into_response();
+        }
+    }
+
+    let format = BoardFormat::negotiate(&headers, &view);
+    let response = save_change(&state, id, &mut game, |game| {
+        reset_response(game, &params, format)
+    })
+    .await;
+
+    if response.status().is_success() {
+        publish(&events, &game, BoardChange::Reset);
+    }
+    response
+}
+
+// Day 12 Handler - place an item into the board of a game session
+#[debug_handler]
+#[tracing::instrument(
+    name = "Day 12 Handler - Place an Item in a Game",
+    skip(state, headers, bearer)
+)]
+pub async fn day12_post_place_game_item(
+    State(state): State<AppState>,
+    Path((id, team, column)): Path<(Uuid, String, String)>,
+    bearer: PlayerToken,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let (game, events) = match state.games.write().await.session(id) {
+        Some(session) => session,
+        None => return game_not_found(id),
+    };
+
+    let mut game = game.write().await;
+
+    let token = bearer.as_ref().map(|TypedHeader(auth)| auth.token());
+    if let Some(violation) = strict_violation(&game, &team, token) {
+        return violation.into_response();
+    }
+
+    let format = BoardFormat::negotiate(&headers, &view);
+    let response = save_change(&state, id, &mut game, |game| {
+        place_response(game, &team, &column, format)
+    })
+    .await;
+
+    if response.status().is_success() {
+        publish_last_move(&events, &game);
+    }
+    response
+}
+
+// Day 12 Handler - let the AI opponent place an item for a team into the board of a game session; in a
+// strict game, the AI plays on behalf of the player seated for the team
+#[debug_handler]
+#[tracing::instrument(
+    name = "Day 12 Handler - AI Move in a Game",
+    skip(state, headers, bearer)
+)]
+pub async fn day12_post_game_ai_move(
+    State(state): State<AppState>,
+    Path((id, team)): Path<(Uuid, String)>,
+    Query(params): Query<AiParameters>,
+    bearer: PlayerToken,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let (game, events) = match state.games.write().await.session(id) {
+        Some(session) => session,
+        None => return game_not_found(id),
+    };
+
+    let snapshot = {
+        let game = game.read().await;
+        let token = bearer.as_ref().map(|TypedHeader(auth)| auth.token());
+        if let Some(violation) = strict_violation(&game, &team, token) {
+            return violation.into_response();
+        }
+        game.clone()
+    };
+    let choice = match ai_choice(snapshot, &team, params.difficulty).await {
+        Ok(choice) => choice,
+        Err(response) => return response,
+    };
+
+    let mut game = game.write().await;
+    let format = BoardFormat::negotiate(&headers, &view);
+    let response = save_change(&state, id, &mut game, |game| {
+        ai_response(game, &choice, format)
+    })
+    .await;
+
+    if response.status().is_success() {
+        publish_last_move(&events, &game);
+    }
+    response
+}
+
+// Day 12 Handler - take back the last move in a game session; in a strict game, only the player who
+// made the move may take it back
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - Undo in a Game", skip(state, headers, bearer))]
+pub async fn day12_post_game_undo(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    bearer: PlayerToken,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let (game, events) = match state.games.write().await.session(id) {
+        Some(session) => session,
+        None => return game_not_found(id),
+    };
+
+    let mut game = game.write().await;
+
+    let token = bearer.as_ref().map(|TypedHeader(auth)| auth.token());
+    if let (Some(seats), Some(last)) = (&game.seats, game.moves.last()) {
+        if !token.is_some_and(|token| seats.is_seated(last.team, token)) {
+            let response_body = "Only the player who made the last move may undo it.".to_string();
+            return (StatusCode::FORBIDDEN, response_body).into_response();
+        }
+    }
+
+    let number = game.moves.len();
+    let last = game.moves.last().copied();
+    let format = BoardFormat::negotiate(&headers, &view);
+    let response = save_change(&state, id, &mut game, |game| undo_response(game, format)).await;
+
+    if let (true, Some(last)) = (response.status().is_success(), last) {
+        let change = BoardChange::Undo(MoveBody::numbered(number, &last));
+        publish(&events, &game, change);
+    }
+    response
+}
+
+// Day 12 Handler - list the moves played in a game session
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - History of a Game", skip(state))]
+pub async fn day12_get_game_history(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let game = match state.games.write().await.get(id) {
+        Some(game) => game,
+        None => return game_not_found(id),
+    };
+
+    let game = game.read().await;
+    history_response(&game)
+}
+
+// Day 12 Handler - the board of a game session as it was after the given number of moves
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - Replay a Game", skip(state, headers))]
+pub async fn day12_get_game_replay(
+    State(state): State<AppState>,
+    Path((id, n)): Path<(Uuid, usize)>,
+    Query(view): Query<ViewParameters>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let game = match state.games.write().await.get(id) {
+        Some(game) => game,
+        None => return game_not_found(id),
+    };
+
+    let game = game.read().await;
+    replay_response(&game, n, BoardFormat::negotiate(&headers, &view))
+}
+
+// Day 12 Handler - stream the board of a game session as server-sent events, like /12/events; watching
+// does not keep the session from expiring
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - Game Events", skip(state))]
+pub async fn day12_get_game_events(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Response {
+    let (game, events) = match state.games.write().await.session(id) {
+        Some(session) => session,
+        None => return game_not_found(id),
+    };
+
+    // subscribe before reading the board, so no change made in between is missed
+    let receiver = events.subscribe();
+    let board = format!("{}", game.read().await);
+    board_events(receiver, board).into_response()
+}
+
+// Day 12 Handler - take the seat for a team in a strict game session, returning the player's token
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - Join Game", skip(state))]
+pub async fn day12_post_join_game(
+    State(state): State<AppState>,
+    Path((id, team)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    let tile = match Tile::from_str(&team) {
+        Ok(tile) => tile,
+        Err(_) => return (StatusCode::BAD_REQUEST).into_response(),
+    };
+
+    let game = match state.games.write().await.get(id) {
+        Some(game) => game,
+        None => return game_not_found(id),
+    };
+
+    let mut game = game.write().await;
+    save_change(&state, id, &mut game, |game| {
+        let seats = match game.seats.as_mut() {
+            Some(seats) => seats,
+            None => {
+                let response_body = "Only strict games have seats to join.".to_string();
+                return (StatusCode::BAD_REQUEST, response_body).into_response();
+            }
+        };
+
+        match seats.join(tile) {
+            Some(token) => (StatusCode::CREATED, Json(SeatBody { team, token })).into_response(),
+            None => {
+                let response_body = format!("The seat for {} is already taken.", team);
+                (StatusCode::CONFLICT, response_body).into_response()
+            }
+        }
+    })
+    .await
+}
+
+// Day 12 Handler - end a game session
+#[debug_handler]
+#[tracing::instrument(name = "Day 12 Handler - Delete Game", skip(state))]
+pub async fn day12_delete_game(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !state.games.write().await.remove(id) {
+        return game_not_found(id);
+    }
+
+    match state.saved_games.delete(id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -232,10 +1738,29 @@ mod tests {
 
     use super::*;
 
+    // build a game from its columns, each listed from the bottom up, where four in a row wins
+    fn game_from_columns(board: Vec<Vec<Tile>>) -> Game {
+        Game {
+            width: board.len(),
+            height: board[0].len(),
+            connect: 4,
+            board,
+            status: None,
+            turn: Tile::Cookie,
+            seats: None,
+            moves: Vec::new(),
+            winning_line: Vec::new(),
+        }
+    }
+
     #[test]
     fn default_method_returns_a_new_game_with_an_empty_board() {
         let test_game = Game::default();
-        assert_eq!(test_game.board, [[Tile::Empty; 4]; 4]);
+        assert_eq!(test_game.board, vec![vec![Tile::Empty; 4]; 4]);
+        assert_eq!(
+            (test_game.width, test_game.height, test_game.connect),
+            (4, 4, 4)
+        );
         assert_eq!(test_game.status, None);
     }
 
@@ -252,55 +1777,286 @@ mod tests {
     #[test]
     fn check_winner_returns_true_for_winning_conditions() {
         // top to bottom winning pattern
-        let test_game = Game {
-            board: [
-                [Tile::Cookie, Tile::Milk, Tile::Milk, Tile::Cookie],
-                [Tile::Cookie, Tile::Cookie, Tile::Cookie, Tile::Cookie],
-                [Tile::Milk, Tile::Milk, Tile::Milk, Tile::Cookie],
-                [Tile::Milk, Tile::Milk, Tile::Cookie, Tile::Cookie],
-            ],
-            status: None,
-        };
+        let test_game = game_from_columns(vec![
+            vec![Tile::Cookie, Tile::Milk, Tile::Milk, Tile::Cookie],
+            vec![Tile::Cookie, Tile::Cookie, Tile::Cookie, Tile::Cookie],
+            vec![Tile::Milk, Tile::Milk, Tile::Milk, Tile::Cookie],
+            vec![Tile::Milk, Tile::Milk, Tile::Cookie, Tile::Cookie],
+        ]);
 
         assert!(test_game.check_winner(1, 1));
 
         // left_to_right winning pattern
-        let test_game = Game {
-            board: [
-                [Tile::Cookie, Tile::Milk, Tile::Milk, Tile::Milk],
-                [Tile::Cookie, Tile::Cookie, Tile::Cookie, Tile::Milk],
-                [Tile::Milk, Tile::Milk, Tile::Milk, Tile::Milk],
-                [Tile::Milk, Tile::Milk, Tile::Cookie, Tile::Cookie],
-            ],
-            status: None,
-        };
+        let test_game = game_from_columns(vec![
+            vec![Tile::Cookie, Tile::Milk, Tile::Milk, Tile::Milk],
+            vec![Tile::Cookie, Tile::Cookie, Tile::Cookie, Tile::Milk],
+            vec![Tile::Milk, Tile::Milk, Tile::Milk, Tile::Milk],
+            vec![Tile::Milk, Tile::Milk, Tile::Cookie, Tile::Cookie],
+        ]);
 
         assert!(test_game.check_winner(2, 0));
 
         // left to right diagonal winning pattern
-        let test_game = Game {
-            board: [
-                [Tile::Cookie, Tile::Milk, Tile::Milk, Tile::Milk],
-                [Tile::Cookie, Tile::Cookie, Tile::Cookie, Tile::Milk],
-                [Tile::Milk, Tile::Milk, Tile::Cookie, Tile::Milk],
-                [Tile::Milk, Tile::Milk, Tile::Cookie, Tile::Cookie],
-            ],
-            status: None,
-        };
+        let test_game = game_from_columns(vec![
+            vec![Tile::Cookie, Tile::Milk, Tile::Milk, Tile::Milk],
+            vec![Tile::Cookie, Tile::Cookie, Tile::Cookie, Tile::Milk],
+            vec![Tile::Milk, Tile::Milk, Tile::Cookie, Tile::Milk],
+            vec![Tile::Milk, Tile::Milk, Tile::Cookie, Tile::Cookie],
+        ]);
 
         assert!(test_game.check_winner(0, 0));
 
         // right to left diagonal winning pattern
+        let test_game = game_from_columns(vec![
+            vec![Tile::Cookie, Tile::Milk, Tile::Milk, Tile::Milk],
+            vec![Tile::Cookie, Tile::Cookie, Tile::Milk, Tile::Milk],
+            vec![Tile::Milk, Tile::Milk, Tile::Milk, Tile::Milk],
+            vec![Tile::Milk, Tile::Milk, Tile::Cookie, Tile::Cookie],
+        ]);
+
+        assert!(test_game.check_winner(3, 0));
+    }
+
+    #[test]
+    fn check_winner_finds_lines_anywhere_on_a_larger_board() {
+        let mut test_game = Game::with_size(7, 6, 4).unwrap();
+
+        // a rising diagonal away from the corners, finished by a tile in the middle of the line
+        for (column, height) in [(2, 1), (4, 3), (5, 4)] {
+            test_game.board[column][height] = Tile::Milk;
+        }
+        test_game.board[3][2] = Tile::Milk;
+
+        assert!(test_game.check_winner(3, 2));
+        assert!(!test_game.check_winner(0, 0));
+    }
+
+    #[test]
+    fn make_move_plays_a_classic_game_to_a_horizontal_win() {
+        let mut test_game = Game::with_size(7, 6, 4).unwrap();
+
+        for column in 3..6 {
+            assert!(test_game.make_move(Tile::Cookie, column));
+            assert!(test_game.make_move(Tile::Milk, column));
+        }
+        assert_eq!(test_game.status, None);
+
+        assert!(test_game.make_move(Tile::Cookie, 6));
+        assert_eq!(test_game.status, Some(Outcome::Cookie));
+        assert!(!test_game.make_move(Tile::Milk, 0));
+    }
+
+    #[test]
+    fn make_move_records_the_winning_line_and_undo_clears_it() {
+        let mut test_game = Game::with_size(7, 6, 4).unwrap();
+        for column in 3..6 {
+            assert!(test_game.make_move(Tile::Cookie, column));
+            assert!(test_game.make_move(Tile::Milk, column));
+        }
+        assert!(test_game.winning_line.is_empty());
+
+        assert!(test_game.make_move(Tile::Cookie, 6));
+        assert_eq!(test_game.winning_line, vec![(3, 0), (4, 0), (5, 0), (6, 0)]);
+
+        let body = BoardBody::from(&test_game);
+        assert_eq!(body.status, BoardStatus::Won);
+        assert_eq!(body.winner, Some(Tile::Cookie));
+        assert_eq!(body.next_player, None);
+        assert_eq!(body.winning_cells[0], CellBody { column: 4, row: 1 });
+
+        let highlighted = format!("{:#}", test_game);
+        assert_eq!(highlighted.matches(WINNING_TILE).count(), 4);
+        assert!(!format!("{}", test_game).contains(WINNING_TILE));
+
+        test_game.undo();
+        assert!(test_game.winning_line.is_empty());
+    }
+
+    #[test]
+    fn board_format_prefers_json_when_accepted_and_highlights_text_on_request() {
+        let mut headers = HeaderMap::new();
+        let plain = ViewParameters::default();
+        let highlighted = ViewParameters { highlight: true };
+        assert_eq!(BoardFormat::negotiate(&headers, &plain), BoardFormat::Text);
+        assert_eq!(
+            BoardFormat::negotiate(&headers, &highlighted),
+            BoardFormat::HighlightedText
+        );
+
+        headers.insert(header::ACCEPT, "text/plain, */*".parse().unwrap());
+        assert_eq!(BoardFormat::negotiate(&headers, &plain), BoardFormat::Text);
+
+        headers.insert(
+            header::ACCEPT,
+            "text/html, Application/JSON;q=0.9".parse().unwrap(),
+        );
+        assert_eq!(BoardFormat::negotiate(&headers, &plain), BoardFormat::Json);
+        assert_eq!(
+            BoardFormat::negotiate(&headers, &highlighted),
+            BoardFormat::Json
+        );
+    }
+
+    #[test]
+    fn with_size_rejects_empty_boards_and_unreachable_win_lengths() {
+        assert!(Game::with_size(0, 6, 4).is_none());
+        assert!(Game::with_size(7, MAX_SIZE + 1, 4).is_none());
+        assert!(Game::with_size(3, 3, 4).is_none());
+        assert!(Game::with_size(3, 3, 0).is_none());
+        assert!(Game::with_size(5, 1, 5).is_some());
+    }
+
+    #[test]
+    fn game_registry_keeps_sessions_apart_and_expires_idle_ones() {
+        let mut registry = GameRegistry::default();
+        let first = registry.create(Game::default());
+        let second = registry.create(Game::default());
+        assert_ne!(first, second);
+        assert_eq!(registry.sessions.len(), 2);
+
+        assert!(registry.remove(first));
+        assert!(registry.get(first).is_none());
+        assert!(registry.get(second).is_some());
+
+        let mut registry = GameRegistry::new(Duration::ZERO);
+        let expired = registry.create(Game::default());
+        assert!(registry.get(expired).is_none());
+        assert!(registry.sessions.is_empty());
+    }
+
+    #[test]
+    fn make_move_passes_the_turn_to_the_opponent() {
+        let mut test_game = Game::default();
+        assert_eq!(test_game.turn, Tile::Cookie);
+
+        assert!(test_game.make_move(Tile::Cookie, 0));
+        assert_eq!(test_game.turn, Tile::Milk);
+    }
+
+    #[test]
+    fn seats_issue_one_token_per_team() {
+        let mut seats = Seats::default();
+        let cookie = seats.join(Tile::Cookie).unwrap();
+
+        assert!(seats.join(Tile::Cookie).is_none());
+        assert!(seats.is_seated(Tile::Cookie, &cookie));
+        assert!(!seats.is_seated(Tile::Milk, &cookie));
+        assert!(seats.is_player(&cookie));
+        assert!(!seats.is_player("stranger"));
+    }
+
+    #[test]
+    fn best_move_takes_an_immediate_win() {
+        // cookie has three along the bottom row, milk three up the last column
+        let test_game = game_from_columns(vec![
+            vec![Tile::Cookie, Tile::Empty, Tile::Empty, Tile::Empty],
+            vec![Tile::Cookie, Tile::Empty, Tile::Empty, Tile::Empty],
+            vec![Tile::Cookie, Tile::Empty, Tile::Empty, Tile::Empty],
+            vec![Tile::Milk, Tile::Milk, Tile::Milk, Tile::Empty],
+        ]);
+
+        assert_eq!(
+            test_game.best_move(Tile::Milk, Difficulty::Medium.depth()),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn best_move_blocks_an_immediate_loss() {
+        // milk threatens to complete the first column on its next move
+        let mut test_game = Game::with_size(7, 6, 4).unwrap();
+        for y in 0..3 {
+            test_game.board[0][y] = Tile::Milk;
+        }
+        test_game.board[3][0] = Tile::Cookie;
+        test_game.board[4][0] = Tile::Cookie;
+
+        assert_eq!(
+            test_game.best_move(Tile::Cookie, Difficulty::Medium.depth()),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn search_gives_up_once_the_budget_runs_out_and_leaves_the_game_as_it_was() {
+        let mut test_game = Game::with_size(MAX_SIZE, MAX_SIZE, 4).unwrap();
+        test_game.make_move(Tile::Milk, 3);
+        let before = test_game.clone();
+
+        let mut budget = SearchBudget { nodes: 100 };
+        assert_eq!(
+            test_game.search(Tile::Cookie, Difficulty::Hard.depth(), &mut budget),
+            None
+        );
+        assert_eq!(budget.nodes, 0);
+        assert_eq!(test_game.board, before.board);
+        assert_eq!(test_game.moves, before.moves);
+        assert_eq!(test_game.status, before.status);
+
+        // the search of the largest board falls back to shallower depths, but still finds a column
+        assert!(before
+            .best_move(Tile::Cookie, Difficulty::Hard.depth())
+            .is_some());
+    }
+
+    #[test]
+    fn best_move_returns_none_once_the_game_is_over() {
         let test_game = Game {
-            board: [
-                [Tile::Cookie, Tile::Milk, Tile::Milk, Tile::Milk],
-                [Tile::Cookie, Tile::Cookie, Tile::Milk, Tile::Milk],
-                [Tile::Milk, Tile::Milk, Tile::Milk, Tile::Milk],
-                [Tile::Milk, Tile::Milk, Tile::Cookie, Tile::Cookie],
-            ],
-            status: None,
+            status: Some(Outcome::Milk),
+            ..Game::default()
         };
 
-        assert!(test_game.check_winner(3, 0));
+        assert_eq!(test_game.best_move(Tile::Cookie, 1), None);
+    }
+
+    #[test]
+    fn random_matches_the_first_board_of_the_day_12_challenge() {
+        let mut rng = StdRng::seed_from_u64(RANDOM_BOARD_SEED);
+        let test_game = Game::random(4, 4, 4, &mut rng).unwrap();
+
+        let expected =
+            "⬜🍪🍪🍪🍪⬜\n⬜🥛🍪🍪🥛⬜\n⬜🥛🥛🥛🥛⬜\n⬜🍪🥛🍪🥛⬜\n⬜⬜⬜⬜⬜⬜\n🍪 wins!\n";
+        assert_eq!(format!("{}", test_game), expected);
+        assert_eq!(test_game.winning_line, vec![(0, 3), (1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn from_tiles_settles_a_full_board_without_a_line_as_a_draw() {
+        let (c, m) = (Tile::Cookie, Tile::Milk);
+        let tiles = [c, m, c, m, m, c, m, c];
+
+        let test_game = Game::from_tiles(2, 4, 4, &tiles).unwrap();
+        assert_eq!(test_game.status, Some(Outcome::Neither));
+        assert!(Game::from_tiles(2, 4, 4, &tiles[1..]).is_none());
+    }
+
+    #[test]
+    fn undo_takes_back_the_winning_move_and_replay_rebuilds_earlier_boards() {
+        let mut test_game = Game::default();
+        for column in [0, 1, 0, 1, 0, 1, 0] {
+            let team = test_game.turn;
+            assert!(test_game.make_move(team, column));
+        }
+        assert_eq!(test_game.status, Some(Outcome::Cookie));
+        assert_eq!(test_game.moves.len(), 7);
+
+        let last = test_game.undo().unwrap();
+        assert_eq!(
+            last,
+            Move {
+                team: Tile::Cookie,
+                column: 0
+            }
+        );
+        assert_eq!(test_game.status, None);
+        assert_eq!(test_game.turn, Tile::Cookie);
+        assert_eq!(test_game.get_tile(0, 3), Tile::Empty);
+        assert_eq!(test_game.get_tile(0, 2), Tile::Cookie);
+
+        let replayed = test_game.replay(2).unwrap();
+        assert_eq!(replayed.get_tile(0, 0), Tile::Cookie);
+        assert_eq!(replayed.get_tile(1, 0), Tile::Milk);
+        assert_eq!(replayed.get_tile(0, 1), Tile::Empty);
+        assert!(test_game.replay(7).is_none());
     }
 }
@@ -1,33 +1,537 @@
 // src/lib/routes/day19.rs
 
 // dependencies
+use crate::errors::AppError;
+use crate::repository::UpdateOutcome;
 use crate::startup::AppState;
 use axum::{
-    extract::{Json, Path, State},
-    http::StatusCode,
+    body::Body,
+    extract::{Json, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
+    BoxError,
 };
 use axum_macros::debug_handler;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
-use sqlx::Row;
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
 use uuid::Uuid;
 
+// number of quotes returned on each page of the /19/list endpoint
+const PAGE_SIZE: i64 = 3;
+
+// length of the continuation token issued by the /19/list endpoint
+const TOKEN_LENGTH: usize = 16;
+
+// how long a continuation token can be used for, and the most tokens kept at once; beyond that, the
+// oldest ones are forgotten first
+const TOKEN_TTL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+const MAX_TOKENS: usize = 10_000;
+
+// maximum number of tags on a single quote, and the maximum length of each tag
+const MAX_TAGS: usize = 10;
+const MAX_TAG_LENGTH: usize = 32;
+
 // struct type to represent the incoming JSON payload contained in the request body
 #[derive(Clone, Debug, Deserialize)]
 pub struct Payload {
     author: String,
     quote: String,
+    #[serde(default)]
+    expected_version: Option<i32>,
+    #[serde(default)]
+    tags: Option<Vec<String>>,
 }
 
 // struct type to represent the API endpoint response body
-#[derive(Serialize)]
+#[derive(Clone, Debug, Serialize, sqlx::FromRow)]
 pub struct ResponseBody {
+    pub(crate) id: Uuid,
+    pub(crate) author: String,
+    pub(crate) quote: String,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) version: i32,
+    pub(crate) tags: Vec<String>,
+}
+
+// struct type to represent the query parameters for the /19/list endpoint
+#[derive(Debug, Deserialize)]
+pub struct ListParameters {
+    token: Option<String>,
+}
+
+// struct type to represent the query parameters for the /19/random and /19/today endpoints
+#[derive(Debug, Deserialize)]
+pub struct PickParameters {
+    author: Option<String>,
+}
+
+// struct type to represent the query parameters for the /19/search endpoint
+#[derive(Debug, Deserialize)]
+pub struct SearchParameters {
+    q: Option<String>,
+    author: Option<String>,
+    token: Option<String>,
+}
+
+// struct type to represent the filters of a full-text search, remembered alongside its continuation tokens
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchFilter {
+    pub(crate) q: Option<String>,
+    pub(crate) author: Option<String>,
+}
+
+// struct type to represent the position in a listing that a continuation token points to
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageCursor {
+    offset: i64,
+    search: Option<SearchFilter>,
+}
+
+// struct type to represent the continuation tokens issued by the /19/list and /19/search endpoints; each
+// one is used up once its page has been fetched, and expires if it never is
+#[derive(Debug, Default)]
+pub struct PageTokens {
+    cursors: HashMap<String, (PageCursor, Instant)>,
+    issued: VecDeque<String>,
+}
+
+// methods for the PageTokens type
+impl PageTokens {
+    // forget the tokens which have expired, along with the oldest ones once there are too many; tokens
+    // are issued in order, so both are at the front of the queue
+    fn evict(&mut self) {
+        while let Some(token) = self.issued.front() {
+            let expired = self
+                .cursors
+                .get(token)
+                .is_none_or(|(_, issued_at)| issued_at.elapsed() >= TOKEN_TTL);
+            if !expired && self.issued.len() < MAX_TOKENS {
+                break;
+            }
+            if let Some(token) = self.issued.pop_front() {
+                self.cursors.remove(&token);
+            }
+        }
+    }
+
+    // issue a new token pointing to the given cursor
+    pub fn issue(&mut self, cursor: PageCursor) -> String {
+        self.evict();
+        let token = generate_token();
+        self.cursors.insert(token.clone(), (cursor, Instant::now()));
+        self.issued.push_back(token.clone());
+        token
+    }
+
+    // the cursor a token points to, if the token has not been used up or expired
+    pub fn get(&mut self, token: &str) -> Option<PageCursor> {
+        self.evict();
+        self.cursors.get(token).map(|(cursor, _)| cursor.clone())
+    }
+
+    // use up a token, once the page it points to has been fetched
+    pub fn remove(&mut self, token: &str) {
+        self.cursors.remove(token);
+    }
+}
+
+// struct type to represent a single page of quotes returned by the /19/list endpoint
+#[derive(Serialize)]
+pub struct ListResponseBody {
+    quotes: Vec<ResponseBody>,
+    page: i64,
+    next_token: Option<String>,
+}
+
+// struct type to represent a single ranked result of the /19/search endpoint
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SearchResultBody {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub(crate) quote: ResponseBody,
+    pub(crate) rank: f32,
+    pub(crate) snippet: String,
+}
+
+// struct type to represent a single page of results returned by the /19/search endpoint
+#[derive(Serialize)]
+pub struct SearchResponseBody {
+    results: Vec<SearchResultBody>,
+    page: i64,
+    next_token: Option<String>,
+}
+
+// enum type to represent the file formats supported by the /19/export and /19/import endpoints
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferFormat {
+    #[default]
+    Jsonl,
+    Csv,
+}
+
+// enum type to represent what the /19/import endpoint does with the ids of imported quotes
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdPolicy {
+    #[default]
+    Preserve,
+    Regenerate,
+}
+
+// struct type to represent the query parameters for the /19/export endpoint
+#[derive(Debug, Deserialize)]
+pub struct ExportParameters {
+    #[serde(default)]
+    format: TransferFormat,
+}
+
+// struct type to represent the query parameters for the /19/import endpoint
+#[derive(Debug, Deserialize)]
+pub struct ImportParameters {
+    #[serde(default)]
+    format: TransferFormat,
+    #[serde(default)]
+    ids: IdPolicy,
+}
+
+// struct type to represent a single quote with all of its columns, as written by the /19/export
+// endpoint and stored by the /19/import endpoint
+#[derive(Clone, Debug, Serialize, sqlx::FromRow)]
+pub struct QuoteRecord {
+    pub(crate) id: Uuid,
+    pub(crate) author: String,
+    pub(crate) quote: String,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) version: i32,
+    pub(crate) deleted_at: Option<DateTime<Utc>>,
+    pub(crate) tags: Vec<String>,
+}
+
+// struct type to represent a single quote as a row of a CSV export, which has no room for a list, so
+// its tags are written as a JSON array
+#[derive(Debug, Serialize)]
+struct CsvQuoteRecord<'a> {
     id: Uuid,
-    author: String,
-    quote: String,
+    author: &'a str,
+    quote: &'a str,
     created_at: DateTime<Utc>,
     version: i32,
+    deleted_at: Option<DateTime<Utc>>,
+    tags: String,
+}
+
+// static constant for the header row of a CSV export, naming the fields of CsvQuoteRecord in order
+const CSV_HEADERS: [&str; 7] = [
+    "id",
+    "author",
+    "quote",
+    "created_at",
+    "version",
+    "deleted_at",
+    "tags",
+];
+
+// enum type to represent the tags of an imported quote, either a list in JSON Lines or the JSON array
+// written in a CSV column
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ImportTags {
+    List(Vec<String>),
+    Text(String),
+}
+
+// struct type to represent a single quote, as read by the /19/import endpoint
+#[derive(Debug, Deserialize)]
+pub struct ImportRecord {
+    id: Option<Uuid>,
+    author: String,
+    quote: String,
+    created_at: Option<DateTime<Utc>>,
+    version: Option<i32>,
+    deleted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    tags: Option<ImportTags>,
+}
+
+// methods for the ImportTags type
+impl ImportTags {
+    // the tags as a list, with an empty CSV column meaning no tags
+    fn into_list(self) -> Result<Vec<String>, String> {
+        match self {
+            Self::List(tags) => Ok(tags),
+            Self::Text(text) if text.trim().is_empty() => Ok(Vec::new()),
+            Self::Text(text) => serde_json::from_str(&text)
+                .map_err(|e| format!("tags must be a JSON array of strings: {}", e)),
+        }
+    }
+}
+
+// struct type to represent a problem with a single line of an import
+#[derive(Debug, Serialize)]
+pub struct ImportError {
+    pub(crate) line: u64,
+    pub(crate) error: String,
+}
+
+// struct type to represent the outcome of an import
+#[derive(Serialize)]
+pub struct ImportResponseBody {
+    imported: usize,
+    errors: Vec<ImportError>,
+}
+
+// struct type to represent a removed quote, as listed by the /19/trash endpoint
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct TrashResponseBody {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub(crate) quote: ResponseBody,
+    pub(crate) deleted_at: DateTime<Utc>,
+}
+
+// struct type to represent a single historical version of a quote
+#[derive(Clone, Debug, Serialize, sqlx::FromRow)]
+pub struct RevisionResponseBody {
+    pub(crate) version: i32,
+    pub(crate) author: String,
+    pub(crate) quote: String,
+    pub(crate) revised_at: DateTime<Utc>,
+}
+
+// struct type to represent a tag and the number of quotes carrying it, as listed by the /19/tags endpoint
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct TagCountBody {
+    pub(crate) tag: String,
+    pub(crate) count: i64,
+}
+
+// struct type to represent every quote carrying a tag, as returned by the /19/tags/{tag} endpoint
+#[derive(Serialize)]
+pub struct TagResponseBody {
+    tag: String,
+    quotes: Vec<ResponseBody>,
+}
+
+// struct type to represent the full revision history of a quote
+#[derive(Serialize)]
+pub struct HistoryResponseBody {
+    id: Uuid,
+    revisions: Vec<RevisionResponseBody>,
+}
+
+// function to build the entity tag for a given version of a quote
+fn etag(id: Uuid, version: i32) -> String {
+    format!("\"{}-{}\"", id, version)
+}
+
+// function to extract the version from an If-Match header, where "*" matches any version; the
+// header is rejected if it is malformed or refers to a different quote
+fn if_match_version(value: &HeaderValue, id: Uuid) -> Result<Option<i32>, ()> {
+    let value = value.to_str().map_err(|_| ())?.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+
+    let tag = value
+        .trim_start_matches("W/")
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or(())?;
+    let (tag_id, tag_version) = tag.rsplit_once('-').ok_or(())?;
+
+    if Uuid::parse_str(tag_id).map_err(|_| ())? != id {
+        return Err(());
+    }
+
+    tag_version.parse::<i32>().map(Some).map_err(|_| ())
+}
+
+// function to normalise the tags of a quote to a sorted, de-duplicated list of lowercase names
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut normalized: Vec<String> = tags.iter().map(|t| t.trim().to_lowercase()).collect();
+    normalized.sort();
+    normalized.dedup();
+
+    if normalized.len() > MAX_TAGS {
+        return Err(AppError::BadRequest(format!(
+            "A quote can carry at most {} tags.",
+            MAX_TAGS
+        )));
+    }
+    if let Some(tag) = normalized
+        .iter()
+        .find(|t| t.is_empty() || t.chars().count() > MAX_TAG_LENGTH)
+    {
+        return Err(AppError::BadRequest(format!(
+            "Tags must be between 1 and {} characters long, got {:?}.",
+            MAX_TAG_LENGTH, tag
+        )));
+    }
+
+    Ok(normalized)
+}
+
+// function to advance a splitmix64 generator, which scatters nearby seeds across the whole range
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// function to derive the starting id for the quote of the day from the UTC date and the author filter,
+// so every caller on the same day lands on the same quote
+fn daily_seed(date: NaiveDate, author: Option<&str>) -> Uuid {
+    // FNV-1a over the author, so each author filter gets its own quote of the day
+    let author_hash = author
+        .unwrap_or_default()
+        .to_lowercase()
+        .bytes()
+        .fold(0xCBF2_9CE4_8422_2325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+        });
+
+    let mut state = date.num_days_from_ce() as u64 ^ author_hash;
+    let high = splitmix64(&mut state);
+    let low = splitmix64(&mut state);
+    Uuid::from_u64_pair(high, low)
+}
+
+// function to generate an opaque, unguessable continuation token
+fn generate_token() -> String {
+    Uuid::new_v4().simple().to_string()[..TOKEN_LENGTH].to_string()
+}
+
+// function to check that a continuation token has the form of one we issued
+fn is_well_formed_token(token: &str) -> bool {
+    token.len() == TOKEN_LENGTH && token.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+// function to look up a continuation token, returning the cursor it points to; unknown, malformed,
+// expired or already used tokens are rejected
+async fn lookup_token(state: &AppState, token: &str) -> Result<PageCursor, AppError> {
+    let unknown_token = || AppError::BadRequest(format!("Unknown continuation token: {}", token));
+
+    if !is_well_formed_token(token) {
+        return Err(unknown_token());
+    }
+
+    state
+        .page_tokens
+        .write()
+        .await
+        .get(token)
+        .ok_or_else(unknown_token)
+}
+
+// function to use up a continuation token once the page it points to has been fetched, so a request
+// which fails before then can be retried with the same token
+async fn use_token(state: &AppState, token: Option<&str>) {
+    if let Some(token) = token {
+        state.page_tokens.write().await.remove(token);
+    }
+}
+
+// function to issue a new continuation token pointing to the given cursor
+async fn issue_token(state: &AppState, cursor: PageCursor) -> String {
+    state.page_tokens.write().await.issue(cursor)
+}
+
+// function to encode the header row of a CSV export
+fn encode_csv_headers() -> Result<Vec<u8>, BoxError> {
+    let mut csv_writer = csv::Writer::from_writer(Vec::new());
+    csv_writer.write_record(CSV_HEADERS)?;
+    Ok(csv_writer.into_inner()?)
+}
+
+// function to encode a quote record as a single line of the requested format; CSV rows are written
+// without a header row, which goes ahead of them
+fn encode_record(record: &QuoteRecord, format: TransferFormat) -> Result<Vec<u8>, BoxError> {
+    match format {
+        TransferFormat::Jsonl => {
+            let mut line = serde_json::to_vec(record)?;
+            line.push(b'\n');
+            Ok(line)
+        }
+        TransferFormat::Csv => {
+            let row = CsvQuoteRecord {
+                id: record.id,
+                author: &record.author,
+                quote: &record.quote,
+                created_at: record.created_at,
+                version: record.version,
+                deleted_at: record.deleted_at,
+                tags: serde_json::to_string(&record.tags)?,
+            };
+            let mut csv_writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(Vec::new());
+            csv_writer.serialize(row)?;
+            Ok(csv_writer.into_inner()?)
+        }
+    }
+}
+
+// function to parse the body of an import into records, along with the line each record came from
+fn parse_import(
+    format: TransferFormat,
+    body: &str,
+) -> (Vec<(u64, ImportRecord)>, Vec<ImportError>) {
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+
+    match format {
+        TransferFormat::Jsonl => {
+            for (n, line) in body.lines().enumerate() {
+                let line_number = n as u64 + 1;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<ImportRecord>(line) {
+                    Ok(record) => records.push((line_number, record)),
+                    Err(e) => errors.push(ImportError {
+                        line: line_number,
+                        error: e.to_string(),
+                    }),
+                }
+            }
+        }
+        TransferFormat::Csv => {
+            let mut reader = csv::Reader::from_reader(body.as_bytes());
+            let headers = match reader.headers() {
+                Ok(headers) => headers.clone(),
+                Err(e) => {
+                    errors.push(ImportError {
+                        line: 1,
+                        error: e.to_string(),
+                    });
+                    return (records, errors);
+                }
+            };
+            for result in reader.records() {
+                let parsed = result.and_then(|record| {
+                    let line_number = record.position().map_or(0, |p| p.line());
+                    record
+                        .deserialize::<ImportRecord>(Some(&headers))
+                        .map(|r| (line_number, r))
+                });
+                match parsed {
+                    Ok(record) => records.push(record),
+                    Err(e) => errors.push(ImportError {
+                        line: e.position().map_or(0, |p| p.line()),
+                        error: e.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+
+    (records, errors)
 }
 
 // Day 19 Handler - Task 1, /19/draft endpoint, adds an entry into the database
@@ -36,48 +540,27 @@ pub struct ResponseBody {
 pub async fn day19_draft(
     State(state): State<AppState>,
     Json(payload): Json<Payload>,
-) -> impl IntoResponse {
-    let id = Uuid::new_v4();
-    let created_at: DateTime<Utc> = Utc::now();
+) -> Result<impl IntoResponse, AppError> {
+    let tags = normalize_tags(payload.tags.unwrap_or_default())?;
+    let response_body = state
+        .quotes
+        .create(&payload.author, &payload.quote, &tags)
+        .await?;
 
-    let query = sqlx::query(
-        "INSERT INTO quotes (id, author, quote, created_at) VALUES ($1, $2, $3, $4) RETURNING *",
-    )
-    .bind(id)
-    .bind(payload.author)
-    .bind(payload.quote)
-    .bind(created_at)
-    .fetch_one(&state.db)
-    .await
-    .unwrap();
-
-    let id: Uuid = query.try_get("id").unwrap();
-    let author: String = query.try_get("author").unwrap();
-    let quote: String = query.try_get("quote").unwrap();
-    let created_at: DateTime<Utc> = query.try_get("created_at").unwrap();
-    let version = query.try_get("version").unwrap();
-
-    let response_body = ResponseBody {
-        id,
-        author,
-        quote,
-        created_at,
-        version,
-    };
-
-    (StatusCode::CREATED, Json(response_body))
+    Ok((
+        StatusCode::CREATED,
+        [(header::ETAG, etag(response_body.id, response_body.version))],
+        Json(response_body),
+    ))
 }
 
-// Day 19 Handler - reset endpoint, resets the database and removes all entries
+// Day 19 Handler - reset endpoint, moves every quote into the trash
 #[debug_handler]
 #[tracing::instrument(name = "Day 19 Handler - /19/reset Endpoint", skip(state))]
-pub async fn day19_reset(State(state): State<AppState>) -> impl IntoResponse {
-    let _query = sqlx::query("TRUNCATE quotes")
-        .execute(&state.db)
-        .await
-        .unwrap();
+pub async fn day19_reset(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    state.quotes.truncate().await?;
 
-    StatusCode::OK
+    Ok(StatusCode::OK)
 }
 
 // Day 19 Handler - cite/{id} endpoint, returns the quote with the specified id
@@ -86,110 +569,528 @@ pub async fn day19_reset(State(state): State<AppState>) -> impl IntoResponse {
 pub async fn day19_cite_by_id(
     State(state): State<AppState>,
     Path(cite_id): Path<Uuid>,
-) -> impl IntoResponse {
-    let id = cite_id;
-    let query = sqlx::query("SELECT * FROM quotes WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.db)
-        .await
-        .unwrap();
-
-    match query {
-        Some(query) => {
-            let id = query.try_get("id").unwrap();
-            let author = query.try_get("author").unwrap();
-            let quote = query.try_get("quote").unwrap();
-            let created_at = query.try_get("created_at").unwrap();
-            let version = query.try_get("version").unwrap();
-
-            let response_body = ResponseBody {
-                id,
-                author,
-                quote,
-                created_at,
-                version,
-            };
-
-            (StatusCode::OK, Json(response_body)).into_response()
-        }
-        None => (StatusCode::NOT_FOUND, "".to_string()).into_response(),
+) -> Result<impl IntoResponse, AppError> {
+    match state.quotes.get(cite_id).await? {
+        Some(response_body) => Ok((
+            StatusCode::OK,
+            [(header::ETAG, etag(response_body.id, response_body.version))],
+            Json(response_body),
+        )),
+        None => Err(AppError::NotFound),
     }
 }
 
-// Day 19 Handler - cite/{id} endpoint, deletes the quote with the specified id and returns the quote
+// Day 19 Handler - remove/{id} endpoint, moves the quote with the specified id into the trash and returns the quote
 #[debug_handler]
 #[tracing::instrument(name = "Day 19 Handler - /19/remove/{id} Endpoint", skip(state))]
 pub async fn day19_remove_by_id(
     State(state): State<AppState>,
     Path(remove_id): Path<Uuid>,
-) -> impl IntoResponse {
-    let id = remove_id;
-    let query = sqlx::query("DELETE FROM quotes WHERE id = $1 RETURNING *")
-        .bind(id)
-        .fetch_optional(&state.db)
-        .await
-        .unwrap();
-
-    match query {
-        Some(query) => {
-            let id = query.try_get("id").unwrap();
-            let author = query.try_get("author").unwrap();
-            let quote = query.try_get("quote").unwrap();
-            let created_at = query.try_get("created_at").unwrap();
-            let version = query.try_get("version").unwrap();
-
-            let deleted_response_body = ResponseBody {
-                id,
-                author,
-                quote,
-                created_at,
-                version,
-            };
-
-            (StatusCode::OK, Json(deleted_response_body)).into_response()
-        }
-
-        None => (StatusCode::NOT_FOUND, "".to_string()).into_response(),
+) -> Result<impl IntoResponse, AppError> {
+    match state.quotes.delete(remove_id).await? {
+        Some(deleted_response_body) => Ok((StatusCode::OK, Json(deleted_response_body))),
+        None => Err(AppError::NotFound),
     }
 }
 
-// Day 19 Handler - cite/{id} endpoint, deletes the quote with the specified id and returns the quote
+// Day 19 Handler - undo/{id} endpoint, updates the quote with the specified id and returns the quote
 #[debug_handler]
 #[tracing::instrument(name = "Day 19 Handler - /19/undo/{id} Endpoint", skip(state))]
 pub async fn day19_undo_by_id(
     State(state): State<AppState>,
     Path(undo_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(payload): Json<Payload>,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, AppError> {
     let id = undo_id;
-    let revised_author = payload.author;
-    let revised_quote = payload.quote;
-    let query = sqlx::query("UPDATE quotes SET (author, quote, version) = ($1, $2, version+1) WHERE id = $3 RETURNING *")
-        .bind(revised_author)
-        .bind(revised_quote)
-        .bind(id)
-        .fetch_optional(&state.db)
-        .await
-        .unwrap();
 
-    match query {
-        Some(query) => {
-            let id = query.try_get("id").unwrap();
-            let author = query.try_get("author").unwrap();
-            let quote = query.try_get("quote").unwrap();
-            let created_at = query.try_get("created_at").unwrap();
-            let version = query.try_get("version").unwrap();
+    // an If-Match header takes precedence over the expected_version field of the payload
+    let if_match = match headers.get(header::IF_MATCH) {
+        Some(value) => match if_match_version(value, id) {
+            Ok(version) => version,
+            Err(_) => {
+                return Err(AppError::PreconditionFailed(
+                    "The If-Match header does not refer to this quote.".to_string(),
+                ))
+            }
+        },
+        None => None,
+    };
+    let expected_version = if_match.or(payload.expected_version);
 
-            let response_body = ResponseBody {
-                id,
-                author,
-                quote,
-                created_at,
-                version,
-            };
+    // tags are only replaced when the payload carries them
+    let tags = payload.tags.map(normalize_tags).transpose()?;
+
+    let outcome = state
+        .quotes
+        .update(
+            id,
+            &payload.author,
+            &payload.quote,
+            tags.as_deref(),
+            expected_version,
+        )
+        .await?;
+
+    match outcome {
+        UpdateOutcome::Updated(response_body) => Ok((
+            StatusCode::OK,
+            [(header::ETAG, etag(response_body.id, response_body.version))],
+            Json(response_body),
+        )),
+        UpdateOutcome::NotFound => Err(AppError::NotFound),
+        UpdateOutcome::VersionMismatch if if_match.is_some() => Err(AppError::PreconditionFailed(
+            "The quote has been modified since the If-Match version.".to_string(),
+        )),
+        UpdateOutcome::VersionMismatch => Err(AppError::Conflict(
+            "The quote has been modified since the expected version.".to_string(),
+        )),
+    }
+}
+
+// Day 19 Handler - random endpoint, returns an arbitrary quote, optionally by the specified author
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/random Endpoint", skip(state))]
+pub async fn day19_random(
+    State(state): State<AppState>,
+    Query(params): Query<PickParameters>,
+) -> Result<impl IntoResponse, AppError> {
+    let author = params.author.filter(|a| !a.trim().is_empty());
+
+    match state.quotes.pick(Uuid::new_v4(), author.as_deref()).await? {
+        Some(response_body) => Ok((
+            StatusCode::OK,
+            [
+                (header::ETAG, etag(response_body.id, response_body.version)),
+                (header::CACHE_CONTROL, "no-store".to_string()),
+            ],
+            Json(response_body),
+        )),
+        None => Err(AppError::NotFound),
+    }
+}
+
+// Day 19 Handler - today endpoint, returns the quote of the day, the same for every caller until midnight UTC
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/today Endpoint", skip(state))]
+pub async fn day19_today(
+    State(state): State<AppState>,
+    Query(params): Query<PickParameters>,
+) -> Result<impl IntoResponse, AppError> {
+    let author = params.author.filter(|a| !a.trim().is_empty());
+    let now = Utc::now();
+    let seed = daily_seed(now.date_naive(), author.as_deref());
+
+    // the quote may be cached until the next quote of the day is picked
+    let midnight = (now.date_naive() + Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .map_or(now, |midnight| midnight.and_utc());
+    let max_age = (midnight - now).num_seconds().max(0);
+
+    match state.quotes.pick(seed, author.as_deref()).await? {
+        Some(response_body) => Ok((
+            StatusCode::OK,
+            [
+                (header::ETAG, etag(response_body.id, response_body.version)),
+                (
+                    header::CACHE_CONTROL,
+                    format!("public, max-age={}", max_age),
+                ),
+            ],
+            Json(response_body),
+        )),
+        None => Err(AppError::NotFound),
+    }
+}
 
-            (StatusCode::OK, Json(response_body)).into_response()
+// Day 19 Handler - list endpoint, returns a page of quotes ordered by creation time
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/list Endpoint", skip(state))]
+pub async fn day19_list(
+    State(state): State<AppState>,
+    Query(params): Query<ListParameters>,
+) -> Result<impl IntoResponse, AppError> {
+    let offset = match &params.token {
+        Some(token) => match lookup_token(&state, token).await? {
+            PageCursor {
+                offset,
+                search: None,
+            } => offset,
+            _ => {
+                return Err(AppError::BadRequest(
+                    "The continuation token was issued by /19/search.".to_string(),
+                ))
+            }
+        },
+        None => 0,
+    };
+
+    // fetch one more quote than the page size, to find out if there is a next page
+    let mut quotes = state.quotes.list(offset, PAGE_SIZE + 1).await?;
+    use_token(&state, params.token.as_deref()).await;
+
+    let has_next_page = quotes.len() as i64 > PAGE_SIZE;
+    quotes.truncate(PAGE_SIZE as usize);
+
+    let next_token = if has_next_page {
+        let cursor = PageCursor {
+            offset: offset + PAGE_SIZE,
+            search: None,
+        };
+        Some(issue_token(&state, cursor).await)
+    } else {
+        None
+    };
+
+    let response_body = ListResponseBody {
+        quotes,
+        page: offset / PAGE_SIZE + 1,
+        next_token,
+    };
+
+    Ok((StatusCode::OK, Json(response_body)))
+}
+
+// Day 19 Handler - history/{id} endpoint, returns every version of the quote with the specified id
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/history/{id} Endpoint", skip(state))]
+pub async fn day19_history_by_id(
+    State(state): State<AppState>,
+    Path(history_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let id = history_id;
+
+    match state.quotes.history(id).await? {
+        Some(revisions) => Ok((StatusCode::OK, Json(HistoryResponseBody { id, revisions }))),
+        None => Err(AppError::NotFound),
+    }
+}
+
+// Day 19 Handler - revert/{id}/{version} endpoint, restores a prior version of a quote as a new version
+#[debug_handler]
+#[tracing::instrument(
+    name = "Day 19 Handler - /19/revert/{id}/{version} Endpoint",
+    skip(state)
+)]
+pub async fn day19_revert_by_id(
+    State(state): State<AppState>,
+    Path((revert_id, revert_version)): Path<(Uuid, i32)>,
+) -> Result<impl IntoResponse, AppError> {
+    match state.quotes.revert(revert_id, revert_version).await? {
+        Some(response_body) => Ok((
+            StatusCode::OK,
+            [(header::ETAG, etag(response_body.id, response_body.version))],
+            Json(response_body),
+        )),
+        None => Err(AppError::NotFound),
+    }
+}
+
+// Day 19 Handler - trash endpoint, returns every removed quote, most recently removed first
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/trash Endpoint", skip(state))]
+pub async fn day19_trash(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let response_body = state.quotes.trash().await?;
+
+    Ok((StatusCode::OK, Json(response_body)))
+}
+
+// Day 19 Handler - restore/{id} endpoint, takes the quote with the specified id back out of the trash
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/restore/{id} Endpoint", skip(state))]
+pub async fn day19_restore_by_id(
+    State(state): State<AppState>,
+    Path(restore_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    match state.quotes.restore(restore_id).await? {
+        Some(response_body) => Ok((
+            StatusCode::OK,
+            [(header::ETAG, etag(response_body.id, response_body.version))],
+            Json(response_body),
+        )),
+        None => Err(AppError::NotFound),
+    }
+}
+
+// Day 19 Handler - purge/{id} endpoint, permanently deletes the quote with the specified id from the trash
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/purge/{id} Endpoint", skip(state))]
+pub async fn day19_purge_by_id(
+    State(state): State<AppState>,
+    Path(purge_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    match state.quotes.purge(purge_id).await? {
+        Some(purged_response_body) => Ok((StatusCode::OK, Json(purged_response_body))),
+        None => Err(AppError::NotFound),
+    }
+}
+
+// Day 19 Handler - purge endpoint, permanently deletes everything in the trash
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/purge Endpoint", skip(state))]
+pub async fn day19_purge(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    state.quotes.purge_all().await?;
+
+    Ok(StatusCode::OK)
+}
+
+// Day 19 Handler - tags endpoint, returns every tag in use with the number of quotes carrying it
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/tags Endpoint", skip(state))]
+pub async fn day19_tags(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let response_body = state.quotes.tags().await?;
+
+    Ok((StatusCode::OK, Json(response_body)))
+}
+
+// Day 19 Handler - tags/{tag} endpoint, returns every quote carrying the specified tag, ordered by creation time
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/tags/{tag} Endpoint", skip(state))]
+pub async fn day19_quotes_by_tag(
+    State(state): State<AppState>,
+    Path(tag): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let tag = tag.trim().to_lowercase();
+    let quotes = state.quotes.quotes_by_tag(&tag).await?;
+
+    if quotes.is_empty() {
+        return Err(AppError::NotFound);
+    }
+
+    Ok((StatusCode::OK, Json(TagResponseBody { tag, quotes })))
+}
+
+// Day 19 Handler - search endpoint, returns a page of quotes matching the search terms, best match first
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/search Endpoint", skip(state))]
+pub async fn day19_search(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParameters>,
+) -> Result<impl IntoResponse, AppError> {
+    // a continuation token carries the filters of the search that issued it
+    let (offset, filter) = match &params.token {
+        Some(token) => match lookup_token(&state, token).await? {
+            PageCursor {
+                offset,
+                search: Some(filter),
+            } => (offset, filter),
+            _ => {
+                return Err(AppError::BadRequest(
+                    "The continuation token was issued by /19/list.".to_string(),
+                ))
+            }
+        },
+        None => {
+            let filter = SearchFilter {
+                q: params.q.filter(|q| !q.trim().is_empty()),
+                author: params.author.filter(|a| !a.trim().is_empty()),
+            };
+            if filter.q.is_none() && filter.author.is_none() {
+                return Err(AppError::BadRequest(
+                    "A search needs a q or author parameter.".to_string(),
+                ));
+            }
+            (0, filter)
         }
-        None => (StatusCode::NOT_FOUND, "".to_string()).into_response(),
+    };
+
+    // fetch one more result than the page size, to find out if there is a next page
+    let mut results = state.quotes.search(&filter, offset, PAGE_SIZE + 1).await?;
+    use_token(&state, params.token.as_deref()).await;
+
+    let has_next_page = results.len() as i64 > PAGE_SIZE;
+    results.truncate(PAGE_SIZE as usize);
+
+    let next_token = if has_next_page {
+        let cursor = PageCursor {
+            offset: offset + PAGE_SIZE,
+            search: Some(filter),
+        };
+        Some(issue_token(&state, cursor).await)
+    } else {
+        None
+    };
+
+    let response_body = SearchResponseBody {
+        results,
+        page: offset / PAGE_SIZE + 1,
+        next_token,
+    };
+
+    Ok((StatusCode::OK, Json(response_body)))
+}
+
+// Day 19 Handler - export endpoint, streams every quote in the book as JSON Lines or CSV
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/export Endpoint", skip(state))]
+pub async fn day19_export(
+    State(state): State<AppState>,
+    Query(params): Query<ExportParameters>,
+) -> impl IntoResponse {
+    let format = params.format;
+
+    // a CSV export always starts with its header row, even when the quote book is empty
+    let headers = match format {
+        TransferFormat::Jsonl => None,
+        TransferFormat::Csv => Some(encode_csv_headers()),
+    };
+
+    // encode each quote as the repository hands it over; once streaming has started, an error can
+    // only cut the response body short
+    let records = state.quotes.export().map(move |record| {
+        record
+            .map_err(BoxError::from)
+            .and_then(|record| encode_record(&record, format))
+    });
+    let body = Body::from_stream(stream::iter(headers).chain(records));
+
+    let (content_type, disposition) = match format {
+        TransferFormat::Jsonl => (
+            "application/x-ndjson",
+            "attachment; filename=\"quotes.jsonl\"",
+        ),
+        TransferFormat::Csv => ("text/csv", "attachment; filename=\"quotes.csv\""),
+    };
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+}
+
+// Day 19 Handler - import endpoint, adds every quote in a JSON Lines or CSV body in a single transaction
+#[debug_handler]
+#[tracing::instrument(name = "Day 19 Handler - /19/import Endpoint", skip(state, body))]
+pub async fn day19_import(
+    State(state): State<AppState>,
+    Query(params): Query<ImportParameters>,
+    body: String,
+) -> Result<impl IntoResponse, AppError> {
+    let (records, mut errors) = parse_import(params.format, &body);
+
+    let mut quotes = Vec::new();
+    for (line, record) in records {
+        let id = match (params.ids, record.id) {
+            (IdPolicy::Preserve, Some(id)) => id,
+            (IdPolicy::Preserve, None) => {
+                errors.push(ImportError {
+                    line,
+                    error: "missing id, import with ids=regenerate to assign new ones".to_string(),
+                });
+                continue;
+            }
+            (IdPolicy::Regenerate, _) => Uuid::new_v4(),
+        };
+
+        let tags = record
+            .tags
+            .map_or(Ok(Vec::new()), ImportTags::into_list)
+            .and_then(|tags| normalize_tags(tags).map_err(|e| e.to_string()));
+        let tags = match tags {
+            Ok(tags) => tags,
+            Err(error) => {
+                errors.push(ImportError { line, error });
+                continue;
+            }
+        };
+
+        quotes.push((
+            line,
+            QuoteRecord {
+                id,
+                author: record.author,
+                quote: record.quote,
+                created_at: record.created_at.unwrap_or_else(Utc::now),
+                version: record.version.unwrap_or(1),
+                deleted_at: record.deleted_at,
+                tags,
+            },
+        ));
+    }
+
+    // the import is all or nothing, any problem leaves the quote book untouched
+    let imported = quotes.len();
+    if errors.is_empty() {
+        errors = state.quotes.import(quotes).await?;
+    }
+
+    if !errors.is_empty() {
+        errors.sort_by_key(|e| e.line);
+        let response_body = ImportResponseBody {
+            imported: 0,
+            errors,
+        };
+        return Ok((StatusCode::BAD_REQUEST, Json(response_body)));
+    }
+
+    let response_body = ImportResponseBody { imported, errors };
+
+    Ok((StatusCode::CREATED, Json(response_body)))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn csv_headers_name_the_fields_of_a_csv_row() {
+        let row = CsvQuoteRecord {
+            id: Uuid::nil(),
+            author: "Santa",
+            quote: "Ho ho ho!",
+            created_at: Utc::now(),
+            version: 1,
+            deleted_at: None,
+            tags: "[]".to_string(),
+        };
+        let mut csv_writer = csv::Writer::from_writer(Vec::new());
+        csv_writer.serialize(row).unwrap();
+        let encoded = String::from_utf8(csv_writer.into_inner().unwrap()).unwrap();
+
+        let headers = String::from_utf8(encode_csv_headers().unwrap()).unwrap();
+        assert_eq!(encoded.lines().next(), headers.lines().next());
+    }
+
+    #[test]
+    fn page_tokens_last_until_used_up_and_are_capped_at_max_tokens() {
+        let cursor = |offset| PageCursor {
+            offset,
+            search: None,
+        };
+        let mut tokens = PageTokens::default();
+
+        let token = tokens.issue(cursor(3));
+        assert_eq!(tokens.get(&token), Some(cursor(3)));
+        assert_eq!(tokens.get(&token), Some(cursor(3)));
+        tokens.remove(&token);
+        assert_eq!(tokens.get(&token), None);
+
+        let oldest = tokens.issue(cursor(0));
+        let issued: Vec<String> = (0..MAX_TOKENS).map(|_| tokens.issue(cursor(6))).collect();
+        assert!(tokens.cursors.len() <= MAX_TOKENS);
+        assert_eq!(tokens.get(&oldest), None);
+        assert_eq!(tokens.get(&issued[MAX_TOKENS - 1]), Some(cursor(6)));
+    }
+
+    #[test]
+    fn daily_seed_is_stable_within_a_day_and_differs_between_days_and_authors() {
+        let christmas = NaiveDate::from_ymd_opt(2024, 12, 25).unwrap();
+        let boxing_day = NaiveDate::from_ymd_opt(2024, 12, 26).unwrap();
+
+        assert_eq!(daily_seed(christmas, None), daily_seed(christmas, None));
+        assert_eq!(
+            daily_seed(christmas, Some("Santa")),
+            daily_seed(christmas, Some("santa"))
+        );
+        assert_ne!(daily_seed(christmas, None), daily_seed(boxing_day, None));
+        assert_ne!(
+            daily_seed(christmas, None),
+            daily_seed(christmas, Some("Santa"))
+        );
     }
 }
@@ -1,90 +1,242 @@
 // src/lib/routes/day9.rs
 
 // dependencies
+use crate::errors::AppError;
+use crate::rate_limit::{client_key, BucketConfig, BucketConfigBody};
+use crate::units::{convert, VolumeUnit, MAX_PRECISION};
 use crate::AppState;
 use axum::{
     extract::{rejection::JsonRejection, Json, State},
-    http::StatusCode,
-    response::IntoResponse,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
 use axum_extra::headers::ContentType;
 use axum_extra::TypedHeader;
 use axum_macros::debug_handler;
-use leaky_bucket::RateLimiter;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tokio::time::Duration;
-
-// common conversion factors
-const LITERS_TO_GALLONS: f32 = 3.78541;
-const LITRES_TO_PINTS: f32 = 1.759754;
+use std::str::FromStr;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
 
 // type aliases
-type JsonPayload = Result<Json<Units>, JsonRejection>;
+type JsonPayload = Result<Json<MilkRequest>, JsonRejection>;
 type RequestContentType = Option<TypedHeader<ContentType>>;
+type ConfigPayload = Result<Json<ConfigRequest>, JsonRejection>;
 
 // Day 9 data structure - enum type to represent units
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Units {
-    Liters(f32),
-    Gallons(f32),
-    Pints(f32),
-    Litres(f32),
+    Liters(f64),
+    Gallons(f64),
+    Pints(f64),
+    Litres(f64),
 }
 
-// Day 9, Task 1 handler
-#[debug_handler]
-#[tracing::instrument(name = "Day 9, Tasks Handler", skip(state, payload))]
-pub async fn day9_tasks(
-    State(state): State<AppState>,
-    content_type: RequestContentType,
-    payload: JsonPayload,
-) -> impl IntoResponse {
-    let rate_limiter = state.rate_limiter.read().await;
-    let milk_bucket = rate_limiter.try_acquire(1);
-    if !milk_bucket {
-        return (StatusCode::TOO_MANY_REQUESTS, "No milk available\n").into_response();
+// struct type to represent a request to convert a volume between any two supported units, rounded to
+// the given number of decimal places if there are any
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConversionRequest {
+    pub from: String,
+    pub to: String,
+    pub value: f64,
+    pub precision: Option<u32>,
+}
+
+// enum type to represent the JSON payloads accepted at /9/milk
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum MilkRequest {
+    Conversion(ConversionRequest),
+    Units(Units),
+}
+
+// struct type to represent the response to a conversion request
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ConversionBody {
+    pub unit: String,
+    pub value: f64,
+}
+
+// methods for the Units type
+impl Units {
+    // the quantity, along with the unit it is given in and the unit it is converted to
+    fn conversion(&self) -> (f64, &'static str, &'static str) {
+        match *self {
+            Units::Liters(quantity) => (quantity, "liters", "gallons"),
+            Units::Gallons(quantity) => (quantity, "gallons", "liters"),
+            Units::Litres(quantity) => (quantity, "litres", "pints"),
+            Units::Pints(quantity) => (quantity, "pints", "litres"),
+        }
+    }
+}
+
+// function to convert the volume in a conversion request, checking its units and precision
+fn convert_request(request: ConversionRequest) -> Result<ConversionBody, AppError> {
+    let from: VolumeUnit = request.from.parse().map_err(AppError::BadRequest)?;
+    let to: VolumeUnit = request.to.parse().map_err(AppError::BadRequest)?;
+    if request
+        .precision
+        .is_some_and(|places| places > MAX_PRECISION)
+    {
+        return Err(AppError::BadRequest(format!(
+            "The precision must be at most {} decimal places.",
+            MAX_PRECISION
+        )));
+    }
+
+    Ok(ConversionBody {
+        unit: to.name.to_string(),
+        value: convert(request.value, from, to, request.precision),
+    })
+}
+
+// struct type to represent a request to change the milk buckets; a missing field keeps its current value
+#[derive(Debug, Deserialize)]
+pub struct ConfigRequest {
+    pub capacity: Option<usize>,
+    pub refill_interval_ms: Option<u64>,
+}
+
+// struct type to represent the state of the calling client's milk bucket
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StatusBody {
+    #[serde(flatten)]
+    pub config: BucketConfigBody,
+    pub level: f64,
+    pub remaining: usize,
+}
+
+// function to check the request carries the admin bearer token, comparing it in constant time so the
+// time taken does not give away how much of it was right
+fn authorize_admin(state: &AppState, headers: &HeaderMap) -> Result<(), AppError> {
+    let Some(admin_token) = &state.admin_token else {
+        return Err(AppError::Forbidden(
+            "The admin endpoints are disabled.".to_string(),
+        ));
+    };
+
+    let bearer_token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match bearer_token {
+        Some(token) if bool::from(token.as_bytes().ct_eq(admin_token.as_bytes())) => Ok(()),
+        _ => Err(AppError::Forbidden(
+            "A valid admin bearer token is required.".to_string(),
+        )),
     }
+}
+
+// function to pour out the milk taken from the bucket, converting its volume if the request asks for it
+fn pour_milk(content_type: RequestContentType, payload: JsonPayload) -> Response {
     if let Some(content_type) = content_type {
         if content_type.to_string() == "application/json" {
-            match payload {
-                Ok(value) => match value.0 {
-                    Units::Liters(quantity) => {
-                        let gallons = quantity / LITERS_TO_GALLONS;
-                        return (StatusCode::OK, Json(json!({"gallons": gallons}))).into_response();
-                    }
-                    Units::Gallons(quantity) => {
-                        let liters = quantity * LITERS_TO_GALLONS;
-                        return (StatusCode::OK, Json(json!({"liters": liters}))).into_response();
-                    }
-                    Units::Litres(quantity) => {
-                        let pints = quantity * LITRES_TO_PINTS;
-                        return (StatusCode::OK, Json(json!({"pints": pints}))).into_response();
-                    }
-                    Units::Pints(quantity) => {
-                        let litres = quantity / LITRES_TO_PINTS;
-                        return (StatusCode::OK, Json(json!({"litres": litres}))).into_response();
-                    }
+            return match payload {
+                Ok(Json(MilkRequest::Conversion(request))) => match convert_request(request) {
+                    Ok(body) => (StatusCode::OK, Json(body)).into_response(),
+                    Err(e) => e.into_response(),
                 },
-                Err(_) => return (StatusCode::BAD_REQUEST).into_response(),
-            }
+                Ok(Json(MilkRequest::Units(units))) => {
+                    let (quantity, from, to) = units.conversion();
+                    let unit = |name: &str| {
+                        VolumeUnit::from_str(name).expect("The legacy units are in the table.")
+                    };
+                    let converted = convert(quantity, unit(from), unit(to), None);
+                    (StatusCode::OK, Json(json!({ to: converted }))).into_response()
+                }
+                Err(_) => (StatusCode::BAD_REQUEST).into_response(),
+            };
         }
     }
 
     (StatusCode::OK, "Milk withdrawn\n").into_response()
 }
 
-// Day 9, Bonus Task Handler
+// Day 9, Task 1 handler - each client draws from its own milk bucket, and every response describes it
+// in the X-RateLimit headers
+#[debug_handler]
+#[tracing::instrument(name = "Day 9, Tasks Handler", skip(state, headers, payload))]
+pub async fn day9_tasks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    content_type: RequestContentType,
+    payload: JsonPayload,
+) -> impl IntoResponse {
+    let client = client_key(&headers, &state.api_keys);
+    let outcome = match state.milk_buckets.try_acquire(&client).await {
+        Ok(outcome) => outcome,
+        Err(e) => return e.into_response(),
+    };
+    if !outcome.allowed {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            outcome.headers(),
+            "No milk available\n",
+        )
+            .into_response();
+    }
+
+    (outcome.headers(), pour_milk(content_type, payload)).into_response()
+}
+
+// Day 9, Bonus Task Handler - fills the client's own milk bucket back up
 #[debug_handler]
-#[tracing::instrument(name = "Day 9, Bonus Task Handler - Top up the Cup", skip(state))]
-pub async fn day9_bonus(State(state): State<AppState>) -> impl IntoResponse {
-    let mut full_milk_bucket = state.rate_limiter.write().await;
-    *full_milk_bucket = RateLimiter::builder()
-        .initial(5)
-        .max(5)
-        .interval(Duration::from_secs(1))
-        .build();
-
-    StatusCode::OK
+#[tracing::instrument(
+    name = "Day 9, Bonus Task Handler - Top up the Cup",
+    skip(state, headers)
+)]
+pub async fn day9_bonus(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<StatusCode, AppError> {
+    let client = client_key(&headers, &state.api_keys);
+    state.milk_buckets.refill(&client).await?;
+
+    Ok(StatusCode::OK)
+}
+
+// Day 9, Config Handler - changes the size and refill rate of every milk bucket, for admins only
+#[debug_handler]
+#[tracing::instrument(name = "Day 9, Config Handler", skip(state, headers, payload))]
+pub async fn day9_put_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: ConfigPayload,
+) -> Result<Json<BucketConfigBody>, AppError> {
+    authorize_admin(&state, &headers)?;
+    let Json(request) = payload.map_err(|rejection| AppError::BadRequest(rejection.body_text()))?;
+
+    let current = state.milk_buckets.config().await?;
+    let config = BucketConfig::new(
+        request.capacity.unwrap_or(current.capacity),
+        request
+            .refill_interval_ms
+            .map(Duration::from_millis)
+            .unwrap_or(current.refill_interval),
+    )
+    .map_err(AppError::BadRequest)?;
+    state.milk_buckets.configure(config).await?;
+
+    Ok(Json(config.into()))
+}
+
+// Day 9, Status Handler - reports how full the client's own milk bucket is, without taking from it
+#[debug_handler]
+#[tracing::instrument(name = "Day 9, Status Handler", skip(state, headers))]
+pub async fn day9_get_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<StatusBody>, AppError> {
+    let client = client_key(&headers, &state.api_keys);
+    let config = state.milk_buckets.config().await?;
+    let level = state.milk_buckets.level(&client).await?;
+
+    Ok(Json(StatusBody {
+        config: config.into(),
+        level,
+        remaining: level.floor() as usize,
+    }))
 }
@@ -1,16 +1,33 @@
 // src/lib/startup.rs
 
 // dependencies
-use crate::routes::day12::Game;
-use crate::routes::day12::{day12_post_place_item, day12_post_reset_board, day_12_get_board_state};
+use crate::bucket_repository::{BucketRepository, InMemoryBucketRepository};
+use crate::errors::AppError;
+use crate::game_repository::{GameRepository, PostgresGameRepository};
+use crate::rate_limit::{RateLimitBudget, RateLimitLayer};
+use crate::repository::{PostgresQuoteRepository, QuoteRepository};
+use crate::routes::day12::{
+    day12_delete_game, day12_get_events, day12_get_game_board_state, day12_get_game_events,
+    day12_get_game_history, day12_get_game_replay, day12_get_history, day12_get_random_board,
+    day12_get_replay, day12_post_ai_move, day12_post_create_game, day12_post_game_ai_move,
+    day12_post_game_undo, day12_post_join_game, day12_post_place_game_item, day12_post_place_item,
+    day12_post_reset_board, day12_post_reset_game_board, day12_post_undo, day_12_get_board_state,
+};
+use crate::routes::day12::{
+    BoardEvent, Game, GameRegistry, BOARD_EVENT_CAPACITY, BOARD_ID, RANDOM_BOARD_SEED,
+};
 use crate::routes::day16::{day16_get_unwrap, day16_post_wrap};
+use crate::routes::day19::PageTokens;
 use crate::routes::day19::{
-    day19_cite_by_id, day19_draft, day19_remove_by_id, day19_reset, day19_undo_by_id,
+    day19_cite_by_id, day19_draft, day19_export, day19_history_by_id, day19_import, day19_list,
+    day19_purge, day19_purge_by_id, day19_quotes_by_tag, day19_random, day19_remove_by_id,
+    day19_reset, day19_restore_by_id, day19_revert_by_id, day19_search, day19_tags, day19_today,
+    day19_trash, day19_undo_by_id,
 };
 use crate::routes::day2::{day2_task1, day2_task2, day2_task3_decrypt, day2_task3_encrypt};
 use crate::routes::day23::{day23_task2, day23_task3, day23_task4};
 use crate::routes::day5::day5_tasks;
-use crate::routes::day9::{day9_bonus, day9_tasks};
+use crate::routes::day9::{day9_bonus, day9_get_status, day9_put_config, day9_tasks};
 use crate::routes::day_minus_one::{day_minus_one_task1, day_minus_one_task2};
 use crate::telemetry::MakeRequestUuid;
 use axum::{
@@ -19,12 +36,14 @@ use axum::{
     Router,
 };
 use axum_macros::FromRef;
-use leaky_bucket::RateLimiter;
+use rand::{rngs::StdRng, SeedableRng};
 use sqlx::PgPool;
+use std::collections::HashSet;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use tower::ServiceBuilder;
 use tower_cookies::CookieManagerLayer;
@@ -34,30 +53,132 @@ use tower_http::{
     trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
 };
 use tracing::Level;
+
+// static constants for the rate limits of the Day 19 routes, which all query the database; most may be
+// called in bursts, while the ones reading or rewriting the whole quote book are held back further
+const DAY19_BUDGET: RateLimitBudget = RateLimitBudget::new(20, Duration::from_millis(100));
+const DAY19_BULK_BUDGET: RateLimitBudget = RateLimitBudget::new(5, Duration::from_secs(2));
+
+// how often the Day 12 game sessions which have gone idle are forgotten, along with their saved games
+const GAME_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
 // struct type to represent application state
 #[derive(Debug, Clone, FromRef)]
 pub struct AppState {
-    pub rate_limiter: Arc<RwLock<RateLimiter>>,
+    pub admin_token: Option<String>,
+    pub api_keys: Arc<HashSet<String>>,
+    pub milk_buckets: Arc<dyn BucketRepository>,
     pub game: Arc<RwLock<Game>>,
-    pub db: PgPool,
+    pub game_events: broadcast::Sender<BoardEvent>,
+    pub board_rng: Arc<RwLock<StdRng>>,
+    pub games: Arc<RwLock<GameRegistry>>,
+    pub page_tokens: Arc<RwLock<PageTokens>>,
+    pub quotes: Arc<dyn QuoteRepository>,
+    pub saved_games: Arc<dyn GameRepository>,
 }
 
 // methods for the AppState type
 impl AppState {
     pub fn new(max: usize, refill: u64, pool: PgPool) -> Self {
-        let rate_limiter = RateLimiter::builder()
-            .initial(max)
-            .max(max)
-            .interval(Duration::from_secs(refill))
-            .build();
+        Self::with_repositories(
+            max,
+            refill,
+            Arc::new(PostgresQuoteRepository::new(pool.clone())),
+            Arc::new(PostgresGameRepository::new(pool)),
+        )
+    }
+
+    // build the application state around any quote and game repositories, such as the in-memory ones
+    // for tests
+    pub fn with_repositories(
+        max: usize,
+        refill: u64,
+        quotes: Arc<dyn QuoteRepository>,
+        saved_games: Arc<dyn GameRepository>,
+    ) -> Self {
+        let milk_buckets = InMemoryBucketRepository::new(max, Duration::from_secs(refill));
 
         let game = Game::default();
+        let (game_events, _) = broadcast::channel(BOARD_EVENT_CAPACITY);
 
         Self {
-            rate_limiter: Arc::new(RwLock::new(rate_limiter)),
+            admin_token: None,
+            api_keys: Arc::new(HashSet::new()),
+            milk_buckets: Arc::new(milk_buckets),
             game: Arc::new(RwLock::new(game)),
-            db: pool,
+            game_events,
+            board_rng: Arc::new(RwLock::new(StdRng::seed_from_u64(RANDOM_BOARD_SEED))),
+            games: Arc::new(RwLock::new(GameRegistry::default())),
+            page_tokens: Arc::new(RwLock::new(PageTokens::default())),
+            quotes,
+            saved_games,
+        }
+    }
+
+    // allow the admin endpoints, such as /9/config, to be called with the given bearer token; they are
+    // refused without one
+    pub fn with_admin_token(mut self, admin_token: Option<String>) -> Self {
+        self.admin_token = admin_token;
+        self
+    }
+
+    // let clients sending one of the given keys in the X-Api-Key header have a milk bucket of their own,
+    // instead of sharing the one for their address; any other key is ignored
+    pub fn with_api_keys(mut self, api_keys: impl IntoIterator<Item = String>) -> Self {
+        self.api_keys = Arc::new(api_keys.into_iter().collect());
+        self
+    }
+
+    // keep the Day 9 milk buckets somewhere other than in memory, such as in Postgres so they are shared
+    // by every instance of the app
+    pub fn with_milk_buckets(mut self, milk_buckets: Arc<dyn BucketRepository>) -> Self {
+        self.milk_buckets = milk_buckets;
+        self
+    }
+
+    // load the Day 12 games saved before a restart back into the board and the game sessions,
+    // returning how many were restored
+    pub async fn restore_games(&self) -> Result<usize, AppError> {
+        let mut games = self.games.write().await;
+        let saved = self.saved_games.load_active(games.idle_timeout()).await?;
+        let restored = saved.len();
+
+        for (id, game) in saved {
+            if id == BOARD_ID {
+                *self.game.write().await = game;
+            } else {
+                games.restore(id, game);
+            }
         }
+
+        Ok(restored)
+    }
+
+    // forget the Day 12 game sessions which have gone idle, along with their saved games, and record when
+    // the others were last played or read, so a session kept alive by reads is restored after a restart
+    pub async fn prune_games(&self) -> Result<(), AppError> {
+        let (idle_times, idle_timeout) = {
+            let mut games = self.games.write().await;
+            games.evict_idle();
+            (games.idle_times(), games.idle_timeout())
+        };
+
+        self.saved_games.touch(&idle_times).await?;
+        self.saved_games.prune(idle_timeout).await
+    }
+
+    // prune the Day 12 games in the background, every GAME_PRUNE_INTERVAL
+    pub fn spawn_game_pruning(&self) -> JoinHandle<()> {
+        let state = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(GAME_PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = state.prune_games().await {
+                    tracing::warn!(error = %e, "Unable to prune the Day 12 games.");
+                }
+            }
+        })
     }
 }
 
@@ -85,6 +206,35 @@ impl Application {
 
         let x_request_id = HeaderName::from_static("x-request-id");
 
+        // day 19 routes, each rate limited on its own for every client
+        let day19_rate_limit = RateLimitLayer::new(DAY19_BUDGET)
+            .with_api_keys(state.api_keys.clone())
+            .with_route_budget("/19/reset", DAY19_BULK_BUDGET)
+            .with_route_budget("/19/export", DAY19_BULK_BUDGET)
+            .with_route_budget("/19/import", DAY19_BULK_BUDGET)
+            .with_route_budget("/19/purge", DAY19_BULK_BUDGET);
+        let day19_routes = Router::new()
+            .route("/19/draft", post(day19_draft))
+            .route("/19/reset", post(day19_reset))
+            .route("/19/cite/:id", get(day19_cite_by_id))
+            .route("/19/remove/:id", delete(day19_remove_by_id))
+            .route("/19/undo/:id", put(day19_undo_by_id))
+            .route("/19/list", get(day19_list))
+            .route("/19/random", get(day19_random))
+            .route("/19/today", get(day19_today))
+            .route("/19/history/:id", get(day19_history_by_id))
+            .route("/19/revert/:id/:version", post(day19_revert_by_id))
+            .route("/19/search", get(day19_search))
+            .route("/19/tags", get(day19_tags))
+            .route("/19/tags/:tag", get(day19_quotes_by_tag))
+            .route("/19/export", get(day19_export))
+            .route("/19/import", post(day19_import))
+            .route("/19/trash", get(day19_trash))
+            .route("/19/restore/:id", post(day19_restore_by_id))
+            .route("/19/purge", delete(day19_purge))
+            .route("/19/purge/:id", delete(day19_purge_by_id))
+            .route_layer(day19_rate_limit);
+
         // api routes
         let api_routes = Router::new()
             .route("/", get(day_minus_one_task1))
@@ -96,19 +246,37 @@ impl Application {
             .route("/5/manifest", post(day5_tasks))
             .route("/9/milk", post(day9_tasks))
             .route("/9/refill", post(day9_bonus))
+            .route("/9/config", put(day9_put_config))
+            .route("/9/status", get(day9_get_status))
             .route("/12/board", get(day_12_get_board_state))
             .route("/12/reset", post(day12_post_reset_board))
             .route("/12/place/:team/:column", post(day12_post_place_item))
+            .route("/12/random-board", get(day12_get_random_board))
+            .route("/12/ai/:team", post(day12_post_ai_move))
+            .route("/12/undo", post(day12_post_undo))
+            .route("/12/history", get(day12_get_history))
+            .route("/12/events", get(day12_get_events))
+            .route("/12/replay/:n", get(day12_get_replay))
+            .route("/12/games", post(day12_post_create_game))
+            .route("/12/games/:id", delete(day12_delete_game))
+            .route("/12/games/:id/board", get(day12_get_game_board_state))
+            .route("/12/games/:id/reset", post(day12_post_reset_game_board))
+            .route("/12/games/:id/join/:team", post(day12_post_join_game))
+            .route("/12/games/:id/ai/:team", post(day12_post_game_ai_move))
+            .route("/12/games/:id/undo", post(day12_post_game_undo))
+            .route("/12/games/:id/history", get(day12_get_game_history))
+            .route("/12/games/:id/events", get(day12_get_game_events))
+            .route("/12/games/:id/replay/:n", get(day12_get_game_replay))
+            .route(
+                "/12/games/:id/place/:team/:column",
+                post(day12_post_place_game_item),
+            )
             .route("/16/wrap", post(day16_post_wrap))
             .route("/16/unwrap", get(day16_get_unwrap))
-            .route("/19/draft", post(day19_draft))
-            .route("/19/reset", post(day19_reset))
-            .route("/19/cite/:id", get(day19_cite_by_id))
-            .route("/19/remove/:id", delete(day19_remove_by_id))
-            .route("/19/undo/:id", put(day19_undo_by_id))
             .route("/23/star", get(day23_task2))
             .route("/23/present/:color", get(day23_task3))
             .route("/23/ornament/:state/:n", get(day23_task4))
+            .merge(day19_routes)
             .with_state(state)
             .layer(CookieManagerLayer::new())
             .layer(
@@ -0,0 +1,148 @@
+// src/lib/units.rs
+
+// dependencies
+use std::str::FromStr;
+
+// largest number of decimal places a converted volume can be rounded to
+pub const MAX_PRECISION: u32 = 10;
+
+// struct type to represent a unit of volume, by its size in liters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VolumeUnit {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub liters: f64,
+}
+
+// static constant for every supported unit of volume; US units are the default where the US and
+// imperial ones differ, except for pints, which have always been imperial at /9/milk
+pub const VOLUME_UNITS: &[VolumeUnit] = &[
+    VolumeUnit {
+        name: "milliliters",
+        aliases: &["ml", "millilitres"],
+        liters: 0.001,
+    },
+    VolumeUnit {
+        name: "liters",
+        aliases: &["l", "litres"],
+        liters: 1.0,
+    },
+    VolumeUnit {
+        name: "cups",
+        aliases: &["cup", "us_cups"],
+        liters: 0.236_588_236_5,
+    },
+    VolumeUnit {
+        name: "fluid_ounces",
+        aliases: &["fl_oz", "us_fluid_ounces"],
+        liters: 0.029_573_529_562_5,
+    },
+    VolumeUnit {
+        name: "imperial_fluid_ounces",
+        aliases: &["imp_fl_oz"],
+        liters: 0.028_413_062_5,
+    },
+    VolumeUnit {
+        name: "pints",
+        aliases: &["imperial_pints"],
+        liters: 0.568_261_25,
+    },
+    VolumeUnit {
+        name: "us_pints",
+        aliases: &[],
+        liters: 0.473_176_473,
+    },
+    VolumeUnit {
+        name: "quarts",
+        aliases: &["qt", "us_quarts"],
+        liters: 0.946_352_946,
+    },
+    VolumeUnit {
+        name: "imperial_quarts",
+        aliases: &[],
+        liters: 1.136_522_5,
+    },
+    VolumeUnit {
+        name: "gallons",
+        aliases: &["gal", "us_gallons"],
+        liters: 3.785_411_784,
+    },
+    VolumeUnit {
+        name: "imperial_gallons",
+        aliases: &[],
+        liters: 4.546_09,
+    },
+    VolumeUnit {
+        name: "barrels",
+        aliases: &["bbl"],
+        liters: 158.987_294_928,
+    },
+];
+
+// implement the FromStr trait for the VolumeUnit type, finding a unit by its name or one of its
+// aliases, in any case
+impl FromStr for VolumeUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        VOLUME_UNITS
+            .iter()
+            .find(|unit| unit.name == name || unit.aliases.contains(&name.as_str()))
+            .copied()
+            .ok_or_else(|| format!("Unknown unit of volume: {}", s))
+    }
+}
+
+// function to convert a volume from one unit to another, rounded to the given number of decimal places
+// if there are any
+pub fn convert(value: f64, from: VolumeUnit, to: VolumeUnit, precision: Option<u32>) -> f64 {
+    let converted = value * from.liters / to.liters;
+    match precision {
+        Some(places) => {
+            let scale = 10_f64.powi(places.min(MAX_PRECISION) as i32);
+            (converted * scale).round() / scale
+        }
+        None => converted,
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn unit(name: &str) -> VolumeUnit {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn units_are_found_by_name_or_alias_in_any_case() {
+        assert_eq!(unit("ml"), unit("Milliliters"));
+        assert_eq!(unit("litres"), unit("liters"));
+        assert!("hogsheads".parse::<VolumeUnit>().is_err());
+    }
+
+    #[test]
+    fn convert_matches_the_original_milk_conversions() {
+        let gallons = convert(5.0, unit("liters"), unit("gallons"), None);
+        assert!((gallons - 1.320_860_2).abs() < 1e-6);
+
+        let pints = convert(2.0, unit("litres"), unit("pints"), None);
+        assert!((pints - 3.519_508).abs() < 1e-5);
+    }
+
+    #[test]
+    fn convert_rounds_to_the_requested_precision() {
+        assert_eq!(
+            convert(1.0, unit("barrels"), unit("gallons"), Some(3)),
+            42.0
+        );
+        assert_eq!(convert(1.0, unit("cups"), unit("ml"), Some(1)), 236.6);
+        assert_eq!(
+            convert(1.0, unit("imperial_quarts"), unit("imp_fl_oz"), Some(0)),
+            40.0
+        );
+    }
+}
@@ -2,6 +2,9 @@
 
 // dependencies
 use crate::helpers::spawn_app;
+use shuttlings_cch24::routes::day12::{GameRegistry, Tile};
+use shuttlings_cch24::AppState;
+use std::time::Duration;
 
 #[tokio::test]
 pub async fn day12_get_board_state_responds_with_current_board_and_200_ok() {
@@ -76,3 +79,815 @@ pub async fn day12_post_place_item_responds_with_board_state_and_200_ok() {
     let expected_body = "⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜🍪⬛⬛⬛⬜\n⬜⬜⬜⬜⬜⬜\n";
     assert_eq!(response_body, expected_body);
 }
+
+#[tokio::test]
+pub async fn day12_reset_board_with_size_parameters_resizes_the_board_and_responds_200_ok() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let reset_response = app
+        .application_client
+        .post(format!(
+            "{}/12/reset?width=7&height=6&connect=4",
+            &app.application_address
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let place_response = app
+        .application_client
+        .post(format!("{}/12/place/milk/7", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let out_of_range_response = app
+        .application_client
+        .post(format!("{}/12/place/milk/8", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let invalid_reset_response = app
+        .application_client
+        .post(format!(
+            "{}/12/reset?width=3&height=3&connect=4",
+            &app.application_address
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(reset_response.status().is_success());
+    let reset_body = reset_response
+        .text()
+        .await
+        .expect("Unable to retrieve response body.");
+    let empty_row = "⬜⬛⬛⬛⬛⬛⬛⬛⬜\n";
+    let expected_body = format!("{}⬜⬜⬜⬜⬜⬜⬜⬜⬜\n", empty_row.repeat(6));
+    assert_eq!(reset_body, expected_body);
+
+    assert!(place_response.status().is_success());
+    let place_body = place_response
+        .text()
+        .await
+        .expect("Unable to retrieve response body.");
+    let expected_body = format!(
+        "{}⬜⬛⬛⬛⬛⬛⬛🥛⬜\n⬜⬜⬜⬜⬜⬜⬜⬜⬜\n",
+        empty_row.repeat(5)
+    );
+    assert_eq!(place_body, expected_body);
+
+    assert_eq!(out_of_range_response.status().as_u16(), 400);
+    assert_eq!(invalid_reset_response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+pub async fn day12_game_sessions_have_independent_boards_and_unknown_games_return_404() {
+    // Arrange
+    let app = spawn_app().await;
+    let mut game_ids = Vec::new();
+    for _ in 0..2 {
+        let response = app
+            .application_client
+            .post(format!("{}/12/games", &app.application_address))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status().as_u16(), 201);
+        let response_body: serde_json::Value = response.json().await.unwrap();
+        game_ids.push(response_body["id"].as_str().unwrap().to_string());
+    }
+
+    // Act
+    let place_response = app
+        .application_client
+        .post(format!(
+            "{}/12/games/{}/place/cookie/1",
+            &app.application_address, game_ids[0]
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let other_board_response = app
+        .application_client
+        .get(format!(
+            "{}/12/games/{}/board",
+            &app.application_address, game_ids[1]
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let delete_response = app
+        .application_client
+        .delete(format!(
+            "{}/12/games/{}",
+            &app.application_address, game_ids[1]
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let deleted_board_response = app
+        .application_client
+        .get(format!(
+            "{}/12/games/{}/board",
+            &app.application_address, game_ids[1]
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(place_response.status().is_success());
+    let place_body = place_response
+        .text()
+        .await
+        .expect("Unable to retrieve response body.");
+    assert_eq!(
+        place_body,
+        "⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜🍪⬛⬛⬛⬜\n⬜⬜⬜⬜⬜⬜\n"
+    );
+
+    assert!(other_board_response.status().is_success());
+    let other_board_body = other_board_response
+        .text()
+        .await
+        .expect("Unable to retrieve response body.");
+    assert_eq!(
+        other_board_body,
+        "⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜⬜⬜⬜⬜⬜\n"
+    );
+
+    assert_eq!(delete_response.status().as_u16(), 204);
+    assert_eq!(deleted_board_response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+pub async fn day12_strict_games_reject_unauthenticated_and_out_of_turn_moves() {
+    // Arrange
+    let app = spawn_app().await;
+    let create_response = app
+        .application_client
+        .post(format!("{}/12/games?strict=true", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let create_body: serde_json::Value = create_response.json().await.unwrap();
+    let game_url = format!(
+        "{}/12/games/{}",
+        &app.application_address,
+        create_body["id"].as_str().unwrap()
+    );
+
+    let mut tokens = Vec::new();
+    for team in ["cookie", "milk"] {
+        let response = app
+            .application_client
+            .post(format!("{}/join/{}", &game_url, team))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status().as_u16(), 201);
+        let response_body: serde_json::Value = response.json().await.unwrap();
+        tokens.push(response_body["token"].as_str().unwrap().to_string());
+    }
+
+    // Act
+    let taken_seat_response = app
+        .application_client
+        .post(format!("{}/join/cookie", &game_url))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let anonymous_response = app
+        .application_client
+        .post(format!("{}/place/cookie/1", &game_url))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let wrong_player_response = app
+        .application_client
+        .post(format!("{}/place/cookie/1", &game_url))
+        .bearer_auth(&tokens[1])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let out_of_turn_response = app
+        .application_client
+        .post(format!("{}/place/milk/1", &game_url))
+        .bearer_auth(&tokens[1])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let in_turn_response = app
+        .application_client
+        .post(format!("{}/place/cookie/1", &game_url))
+        .bearer_auth(&tokens[0])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let repeated_turn_response = app
+        .application_client
+        .post(format!("{}/place/cookie/1", &game_url))
+        .bearer_auth(&tokens[0])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(taken_seat_response.status().as_u16(), 409);
+    assert_eq!(anonymous_response.status().as_u16(), 403);
+    assert_eq!(wrong_player_response.status().as_u16(), 403);
+    assert_eq!(out_of_turn_response.status().as_u16(), 409);
+    assert!(in_turn_response.status().is_success());
+    assert_eq!(repeated_turn_response.status().as_u16(), 409);
+}
+
+#[tokio::test]
+pub async fn day12_ai_move_places_an_item_for_the_team_and_responds_200_ok() {
+    // Arrange
+    let app = spawn_app().await;
+    for column in [1, 2, 3] {
+        let response = app
+            .application_client
+            .post(format!(
+                "{}/12/place/milk/{}",
+                &app.application_address, column
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(response.status().is_success());
+    }
+
+    // Act
+    let response = app
+        .application_client
+        .post(format!(
+            "{}/12/ai/cookie?difficulty=hard",
+            &app.application_address
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert the AI blocked milk's bottom row in the last column
+    assert!(response.status().is_success());
+    assert_eq!(response.headers().get("x-ai-column").unwrap(), "4");
+    let response_body = response
+        .text()
+        .await
+        .expect("Unable to retrieve response body.");
+    let expected_body = "⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜🥛🥛🥛🍪⬜\n⬜⬜⬜⬜⬜⬜\n";
+    assert_eq!(response_body, expected_body);
+}
+
+#[tokio::test]
+pub async fn day12_history_undo_and_replay_track_the_moves_played() {
+    // Arrange
+    let app = spawn_app().await;
+    for (team, column) in [("cookie", 1), ("milk", 2)] {
+        let response = app
+            .application_client
+            .post(format!(
+                "{}/12/place/{}/{}",
+                &app.application_address, team, column
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(response.status().is_success());
+    }
+
+    // Act
+    let history_response = app
+        .application_client
+        .get(format!("{}/12/history", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let replay_response = app
+        .application_client
+        .get(format!("{}/12/replay/1", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let future_replay_response = app
+        .application_client
+        .get(format!("{}/12/replay/3", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let undo_response = app
+        .application_client
+        .post(format!("{}/12/undo", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(history_response.status().is_success());
+    let history_body: serde_json::Value = history_response.json().await.unwrap();
+    assert_eq!(
+        history_body,
+        serde_json::json!({
+            "moves": [
+                {"number": 1, "team": "cookie", "column": 1},
+                {"number": 2, "team": "milk", "column": 2}
+            ]
+        })
+    );
+
+    let one_cookie = "⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜⬛⬛⬛⬛⬜\n⬜🍪⬛⬛⬛⬜\n⬜⬜⬜⬜⬜⬜\n";
+    assert!(replay_response.status().is_success());
+    assert_eq!(replay_response.text().await.unwrap(), one_cookie);
+
+    assert_eq!(future_replay_response.status().as_u16(), 404);
+
+    assert!(undo_response.status().is_success());
+    assert_eq!(undo_response.text().await.unwrap(), one_cookie);
+}
+
+#[tokio::test]
+pub async fn day12_board_endpoints_respond_with_json_when_it_is_accepted() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let reset_response = app
+        .application_client
+        .post(format!("{}/12/reset", &app.application_address))
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let place_response = app
+        .application_client
+        .post(format!("{}/12/place/cookie/2", &app.application_address))
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let board_response = app
+        .application_client
+        .get(format!("{}/12/board", &app.application_address))
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let text_response = app
+        .application_client
+        .get(format!("{}/12/board", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(reset_response.status().is_success());
+    let reset_body: serde_json::Value = reset_response.json().await.unwrap();
+    assert_eq!(reset_body["status"], "in_progress");
+    assert_eq!(reset_body["next_player"], "cookie");
+
+    assert!(place_response.status().is_success());
+    assert_eq!(
+        place_response.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+
+    assert!(board_response.status().is_success());
+    let board_body: serde_json::Value = board_response.json().await.unwrap();
+    let empty_column = serde_json::json!(["empty", "empty", "empty", "empty"]);
+    assert_eq!(
+        board_body,
+        serde_json::json!({
+            "width": 4,
+            "height": 4,
+            "connect": 4,
+            "columns": [
+                empty_column,
+                ["cookie", "empty", "empty", "empty"],
+                empty_column,
+                empty_column
+            ],
+            "status": "in_progress",
+            "winner": null,
+            "next_player": "milk",
+            "winning_cells": []
+        })
+    );
+
+    let text_body = text_response.text().await.unwrap();
+    assert!(text_body.starts_with("⬜⬛⬛⬛⬛⬜\n"));
+}
+
+#[tokio::test]
+pub async fn day12_board_marks_the_winning_line_when_highlighted() {
+    // Arrange
+    let app = spawn_app().await;
+    for _ in 0..4 {
+        let response = app
+            .application_client
+            .post(format!("{}/12/place/cookie/1", &app.application_address))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(response.status().is_success());
+    }
+
+    // Act
+    let plain_response = app
+        .application_client
+        .get(format!("{}/12/board", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let highlighted_response = app
+        .application_client
+        .get(format!(
+            "{}/12/board?highlight=true",
+            &app.application_address
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let json_response = app
+        .application_client
+        .get(format!("{}/12/board", &app.application_address))
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let plain_body = plain_response.text().await.unwrap();
+    assert_eq!(
+        plain_body,
+        "⬜🍪⬛⬛⬛⬜\n⬜🍪⬛⬛⬛⬜\n⬜🍪⬛⬛⬛⬜\n⬜🍪⬛⬛⬛⬜\n⬜⬜⬜⬜⬜⬜\n🍪 wins!\n"
+    );
+
+    let highlighted_body = highlighted_response.text().await.unwrap();
+    assert_eq!(
+        highlighted_body,
+        "⬜⭐⬛⬛⬛⬜\n⬜⭐⬛⬛⬛⬜\n⬜⭐⬛⬛⬛⬜\n⬜⭐⬛⬛⬛⬜\n⬜⬜⬜⬜⬜⬜\n🍪 wins!\n"
+    );
+
+    let json_body: serde_json::Value = json_response.json().await.unwrap();
+    assert_eq!(
+        json_body["winning_cells"],
+        serde_json::json!([
+            {"column": 1, "row": 1},
+            {"column": 1, "row": 2},
+            {"column": 1, "row": 3},
+            {"column": 1, "row": 4}
+        ])
+    );
+}
+
+#[tokio::test]
+pub async fn day12_events_stream_the_board_and_each_move_made_to_it() {
+    // Arrange
+    let app = spawn_app().await;
+    let mut events = app
+        .application_client
+        .get(format!("{}/12/events", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert!(events.status().is_success());
+    assert_eq!(
+        events.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+
+    // read the stream until it holds the given text, or give up after a few seconds
+    let mut received = String::new();
+    let mut read_until = async |expected: &str| {
+        while !received.contains(expected) {
+            let chunk = tokio::time::timeout(Duration::from_secs(5), events.chunk())
+                .await
+                .expect("Timed out waiting for an event.")
+                .expect("Failed to read the event stream.")
+                .expect("The event stream ended.");
+            received.push_str(&String::from_utf8_lossy(&chunk));
+        }
+    };
+    read_until("event: board\ndata: ⬜⬛⬛⬛⬛⬜\n").await;
+
+    // Act
+    let response = app
+        .application_client
+        .post(format!("{}/12/place/milk/3", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert!(response.status().is_success());
+
+    // Assert
+    read_until("data: ⬜⬛⬛🥛⬛⬜\n").await;
+    assert!(received.contains(
+        "event: move\ndata: {\"kind\":\"move\",\"number\":1,\"team\":\"milk\",\"column\":3}\n"
+    ));
+}
+
+#[tokio::test]
+pub async fn day12_game_events_stream_the_moves_made_in_that_game_only() {
+    // Arrange
+    let app = spawn_app().await;
+    let response = app
+        .application_client
+        .post(format!("{}/12/games", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response_body: serde_json::Value = response.json().await.unwrap();
+    let id = response_body["id"].as_str().unwrap().to_string();
+
+    let mut events = app
+        .application_client
+        .get(format!(
+            "{}/12/games/{}/events",
+            &app.application_address, id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert!(events.status().is_success());
+
+    // read the stream until it holds the given text, or give up after a few seconds
+    let mut received = String::new();
+    let mut read_until = async |expected: &str| {
+        while !received.contains(expected) {
+            let chunk = tokio::time::timeout(Duration::from_secs(5), events.chunk())
+                .await
+                .expect("Timed out waiting for an event.")
+                .expect("Failed to read the event stream.")
+                .expect("The event stream ended.");
+            received.push_str(&String::from_utf8_lossy(&chunk));
+        }
+    };
+    read_until("event: board\ndata: ⬜⬛⬛⬛⬛⬜\n").await;
+
+    // Act
+    for path in [
+        "12/place/milk/1".to_string(),
+        format!("12/games/{}/place/cookie/2", id),
+        format!("12/games/{}/undo", id),
+    ] {
+        let response = app
+            .application_client
+            .post(format!("{}/{}", &app.application_address, path))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(response.status().is_success());
+    }
+
+    // Assert
+    read_until("event: undo\n").await;
+    assert!(received.contains(
+        "event: move\ndata: {\"kind\":\"move\",\"number\":1,\"team\":\"cookie\",\"column\":2}\n"
+    ));
+    assert!(!received.contains("🥛"));
+
+    let response = app
+        .application_client
+        .get(format!(
+            "{}/12/games/{}/events",
+            &app.application_address,
+            uuid::Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+pub async fn day12_games_are_saved_and_restored_after_a_restart() {
+    // Arrange
+    let app = spawn_app().await;
+    for path in ["place/cookie/1", "place/milk/2", "undo"] {
+        let response = app
+            .application_client
+            .post(format!("{}/12/{}", &app.application_address, path))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(response.status().is_success());
+    }
+
+    let created: serde_json::Value = app
+        .application_client
+        .post(format!("{}/12/games?strict=true", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+
+    let seat: serde_json::Value = app
+        .application_client
+        .post(format!(
+            "{}/12/games/{}/join/cookie",
+            &app.application_address, id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .unwrap();
+    let token = seat["token"].as_str().unwrap();
+
+    let response = app
+        .application_client
+        .post(format!(
+            "{}/12/games/{}/place/cookie/3",
+            &app.application_address, id
+        ))
+        .bearer_auth(token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert!(response.status().is_success());
+
+    // Act
+    let restarted_state = AppState::with_repositories(
+        5,
+        1,
+        app.application_state.quotes.clone(),
+        app.application_state.saved_games.clone(),
+    );
+    let restored = restarted_state.restore_games().await.unwrap();
+
+    // Assert
+    assert_eq!(restored, 2);
+
+    let board = restarted_state.game.read().await;
+    assert_eq!(board.moves.len(), 1);
+    assert_eq!(board.get_tile(0, 0), Tile::Cookie);
+    assert_eq!(board.turn, Tile::Milk);
+
+    let game = restarted_state
+        .games
+        .write()
+        .await
+        .get(id.parse().unwrap())
+        .expect("The game session was not restored.");
+    let game = game.read().await;
+    assert_eq!(game.get_tile(2, 0), Tile::Cookie);
+    assert!(game.seats.as_ref().unwrap().is_seated(Tile::Cookie, token));
+}
+
+#[tokio::test]
+pub async fn day12_pruning_forgets_idle_games_and_keeps_the_ones_still_being_read() {
+    // Arrange
+    let app = spawn_app().await;
+    *app.application_state.games.write().await = GameRegistry::new(Duration::from_millis(500));
+
+    let mut ids = Vec::new();
+    for _ in 0..2 {
+        let created: serde_json::Value = app
+            .application_client
+            .post(format!("{}/12/games", &app.application_address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json()
+            .await
+            .unwrap();
+        ids.push(created["id"].as_str().unwrap().to_string());
+    }
+
+    // only the first game is read, and never played again after it was saved
+    for _ in 0..3 {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let response = app
+            .application_client
+            .get(format!(
+                "{}/12/games/{}/board",
+                &app.application_address, ids[0]
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(response.status().is_success());
+    }
+
+    // Act
+    app.application_state.prune_games().await.unwrap();
+
+    // Assert
+    let response = app
+        .application_client
+        .get(format!(
+            "{}/12/games/{}/board",
+            &app.application_address, ids[1]
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status().as_u16(), 404);
+
+    let saved = app
+        .application_state
+        .saved_games
+        .load_active(Duration::from_millis(500))
+        .await
+        .unwrap();
+    let saved_ids: Vec<String> = saved.iter().map(|(id, _)| id.to_string()).collect();
+    assert!(saved_ids.contains(&ids[0]));
+    assert!(!saved_ids.contains(&ids[1]));
+}
+
+#[tokio::test]
+pub async fn day12_random_board_is_drawn_from_a_seeded_generator_that_restarts_on_reset() {
+    // Arrange
+    let app = spawn_app().await;
+    let first_board =
+        "⬜🍪🍪🍪🍪⬜\n⬜🥛🍪🍪🥛⬜\n⬜🥛🥛🥛🥛⬜\n⬜🍪🥛🍪🥛⬜\n⬜⬜⬜⬜⬜⬜\n🍪 wins!\n";
+    let random_board = |query: &'static str| {
+        app.application_client
+            .get(format!(
+                "{}/12/random-board{}",
+                &app.application_address, query
+            ))
+            .send()
+    };
+
+    // Act
+    let first_response = random_board("").await.expect("Failed to execute request.");
+    let seeded_response = random_board("?seed=7")
+        .await
+        .expect("Failed to execute request.");
+    let second_response = random_board("").await.expect("Failed to execute request.");
+    let reseeded_response = random_board("?seed=7")
+        .await
+        .expect("Failed to execute request.");
+
+    let reset_response = app
+        .application_client
+        .post(format!("{}/12/reset", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert!(reset_response.status().is_success());
+    let after_reset_response = random_board("").await.expect("Failed to execute request.");
+
+    let place_response = app
+        .application_client
+        .post(format!("{}/12/place/milk/1", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(first_response.status().is_success());
+    assert_eq!(first_response.text().await.unwrap(), first_board);
+
+    let seeded_board = seeded_response.text().await.unwrap();
+    assert_eq!(reseeded_response.text().await.unwrap(), seeded_board);
+
+    let second_board = second_response.text().await.unwrap();
+    assert_ne!(second_board, first_board);
+    assert!(second_board.ends_with("wins!\n") || second_board.ends_with("No winner.\n"));
+
+    assert_eq!(after_reset_response.text().await.unwrap(), first_board);
+
+    // the random board is full and already won, so no more items fit
+    assert_eq!(place_response.status().as_u16(), 503);
+
+    // the random board survives a restart even though no moves built it
+    let restarted_state = AppState::with_repositories(
+        5,
+        1,
+        app.application_state.quotes.clone(),
+        app.application_state.saved_games.clone(),
+    );
+    restarted_state.restore_games().await.unwrap();
+    assert_eq!(
+        format!("{}", restarted_state.game.read().await),
+        first_board
+    );
+}
@@ -1,7 +1,7 @@
 // tests/api/day19.rs
 
 // dependencies
-use crate::helpers::spawn_app;
+use crate::helpers::{spawn_app, spawn_app_in_memory};
 use axum::http::StatusCode;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
@@ -18,6 +18,86 @@ struct ApiResponse {
     version: i32,
 }
 
+// struct type to represent a single result in the search endpoint ApiResponse
+#[derive(Deserialize, Debug)]
+struct SearchResultApiResponse {
+    author: String,
+    quote: String,
+    snippet: String,
+}
+
+// struct type to represent the ApiResponse for the search endpoint
+#[derive(Deserialize, Debug)]
+struct SearchApiResponse {
+    results: Vec<SearchResultApiResponse>,
+    page: i64,
+    next_token: Option<String>,
+}
+
+// struct type to represent a single line error in the import endpoint ApiResponse
+#[derive(Deserialize, Debug)]
+struct ImportErrorApiResponse {
+    line: u64,
+}
+
+// struct type to represent the ApiResponse for the import endpoint
+#[derive(Deserialize, Debug)]
+struct ImportApiResponse {
+    imported: usize,
+    errors: Vec<ImportErrorApiResponse>,
+}
+
+// struct type to represent a removed quote in the trash endpoint ApiResponse
+#[derive(Deserialize, Debug)]
+struct TrashApiResponse {
+    id: String,
+    deleted_at: String,
+}
+
+// struct type to represent a single revision in the history endpoint ApiResponse
+#[derive(Deserialize, Debug)]
+struct RevisionApiResponse {
+    version: i32,
+    author: String,
+    quote: String,
+}
+
+// struct type to represent the ApiResponse for the history endpoint
+#[derive(Deserialize, Debug)]
+struct HistoryApiResponse {
+    id: String,
+    revisions: Vec<RevisionApiResponse>,
+}
+
+// struct type to represent the problem body returned for errors
+#[derive(Deserialize, Debug)]
+struct ProblemApiResponse {
+    title: String,
+    status: u16,
+}
+
+// struct type to represent a single tag in the tags endpoint ApiResponse
+#[derive(Deserialize, Debug)]
+struct TagCountApiResponse {
+    tag: String,
+    count: i64,
+}
+
+// struct type to represent the ApiResponse for the tags/{tag} endpoint
+#[derive(Deserialize, Debug)]
+struct TagApiResponse {
+    tag: String,
+    quotes: Vec<ApiResponse>,
+}
+
+// struct type to represent the ApiResponse for the list endpoint
+#[derive(Deserialize, Debug)]
+struct ListApiResponse {
+    quotes: Vec<ApiResponse>,
+    page: i64,
+    next_token: Option<String>,
+}
+
 #[tokio::test]
 async fn day19_draft_endpoint_adds_quote_with_uuid_and_returns_the_quote_with_201_created() {
     // Arrange
@@ -151,3 +231,907 @@ async fn day19_reset_endpoint_clears_the_quotes_table_and_returns_200_ok() {
         .expect("Failed to read response body.");
     assert!(reset_response_body.is_empty());
 }
+
+#[tokio::test]
+async fn day19_list_endpoint_returns_pages_of_quotes_with_continuation_token_and_200_ok() {
+    // Arrange
+    let app = spawn_app().await;
+    for n in 1..=4 {
+        let request_body = json!({
+          "author":"Santa",
+          "quote": format!("Ho ho ho! #{}", n)
+        });
+        app.application_client
+            .post(format!("{}/19/draft", &app.application_address))
+            .json(&request_body)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+    }
+
+    // Act - Part 1
+    let first_response = app
+        .application_client
+        .get(format!("{}/19/list", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert - first page
+    assert_eq!(first_response.status(), StatusCode::OK);
+    let first_page: ListApiResponse = first_response.json().await.unwrap();
+    assert_eq!(first_page.page, 1);
+    assert_eq!(first_page.quotes.len(), 3);
+    assert_eq!(first_page.quotes[0].quote, "Ho ho ho! #1");
+    let token = first_page
+        .next_token
+        .expect("Expected a continuation token.");
+    assert_eq!(token.len(), 16);
+
+    // Act - Part 2
+    let second_response = app
+        .application_client
+        .get(format!(
+            "{}/19/list?token={}",
+            &app.application_address, token
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert - second and last page
+    assert_eq!(second_response.status(), StatusCode::OK);
+    let second_page: ListApiResponse = second_response.json().await.unwrap();
+    assert_eq!(second_page.page, 2);
+    assert_eq!(second_page.quotes.len(), 1);
+    assert_eq!(second_page.quotes[0].quote, "Ho ho ho! #4");
+    assert!(second_page.next_token.is_none());
+
+    // Act - Part 3
+    let reused_response = app
+        .application_client
+        .get(format!(
+            "{}/19/list?token={}",
+            &app.application_address, token
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert - a token can only be used once
+    assert_eq!(reused_response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn day19_list_endpoint_rejects_unknown_or_malformed_tokens_with_400_bad_request() {
+    // Arrange
+    let app = spawn_app().await;
+
+    for token in ["0123456789abcdef", "not-a-token"] {
+        // Act
+        let response = app
+            .application_client
+            .get(format!(
+                "{}/19/list?token={}",
+                &app.application_address, token
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn day19_history_and_revert_endpoints_track_and_restore_prior_versions_with_200_ok() {
+    // Arrange
+    let app = spawn_app().await;
+    let request_body = json!({
+      "author":"Santa",
+      "quote":"Ho ho ho!"
+    });
+    let undo_body = json!({
+      "author":"Grinch",
+      "quote":"Bah humbug!"
+    });
+
+    let response = app
+        .application_client
+        .post(format!("{}/19/draft", &app.application_address))
+        .json(&request_body)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response_data: ApiResponse = response.json().await.unwrap();
+    let id = response_data.id;
+
+    app.application_client
+        .put(format!("{}/19/undo/{}", &app.application_address, id))
+        .json(&undo_body)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Act - Part 1
+    let revert_response = app
+        .application_client
+        .post(format!("{}/19/revert/{}/1", &app.application_address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert - the original text is restored as a new version
+    assert_eq!(revert_response.status(), StatusCode::OK);
+    let revert_data: ApiResponse = revert_response.json().await.unwrap();
+    assert_eq!(revert_data.author, "Santa");
+    assert_eq!(revert_data.quote, "Ho ho ho!");
+    assert_eq!(revert_data.version, 3);
+
+    // Act - Part 2
+    let history_response = app
+        .application_client
+        .get(format!("{}/19/history/{}", &app.application_address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert - every version is present, in order
+    assert_eq!(history_response.status(), StatusCode::OK);
+    let history_data: HistoryApiResponse = history_response.json().await.unwrap();
+    assert_eq!(history_data.id, id);
+    let versions: Vec<i32> = history_data.revisions.iter().map(|r| r.version).collect();
+    assert_eq!(versions, vec![1, 2, 3]);
+    assert_eq!(history_data.revisions[1].author, "Grinch");
+    assert_eq!(history_data.revisions[1].quote, "Bah humbug!");
+
+    // Act - Part 3
+    let missing_response = app
+        .application_client
+        .post(format!("{}/19/revert/{}/42", &app.application_address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert - unknown versions are not found
+    assert_eq!(missing_response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn day19_undo_endpoint_rejects_stale_versions_with_412_or_409_and_returns_etag() {
+    // Arrange
+    let app = spawn_app().await;
+    let request_body = json!({
+      "author":"Santa",
+      "quote":"Ho ho ho!"
+    });
+
+    let response = app
+        .application_client
+        .post(format!("{}/19/draft", &app.application_address))
+        .json(&request_body)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let etag = response
+        .headers()
+        .get("ETag")
+        .expect("Expected an ETag header.")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let response_data: ApiResponse = response.json().await.unwrap();
+    let id = response_data.id;
+    assert_eq!(etag, format!("\"{}-1\"", id));
+
+    // Act - Part 1, a matching If-Match header succeeds
+    let first_update = app
+        .application_client
+        .put(format!("{}/19/undo/{}", &app.application_address, id))
+        .header("If-Match", &etag)
+        .json(&json!({"author":"Santa", "quote":"First edit"}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(first_update.status(), StatusCode::OK);
+    assert_eq!(
+        first_update
+            .headers()
+            .get("ETag")
+            .unwrap()
+            .to_str()
+            .unwrap(),
+        format!("\"{}-2\"", id)
+    );
+
+    // Act - Part 2, the now stale If-Match header is rejected
+    let stale_header_update = app
+        .application_client
+        .put(format!("{}/19/undo/{}", &app.application_address, id))
+        .header("If-Match", &etag)
+        .json(&json!({"author":"Santa", "quote":"Second edit"}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(
+        stale_header_update.status(),
+        StatusCode::PRECONDITION_FAILED
+    );
+
+    // Act - Part 3, a stale expected_version in the payload is rejected
+    let stale_payload_update = app
+        .application_client
+        .put(format!("{}/19/undo/{}", &app.application_address, id))
+        .json(&json!({"author":"Santa", "quote":"Second edit", "expected_version": 1}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(stale_payload_update.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn day19_remove_endpoint_moves_quote_to_trash_where_it_can_be_restored_or_purged() {
+    // Arrange
+    let app = spawn_app().await;
+    let request_body = json!({
+      "author":"Santa",
+      "quote":"Ho ho ho!"
+    });
+
+    let response = app
+        .application_client
+        .post(format!("{}/19/draft", &app.application_address))
+        .json(&request_body)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response_data: ApiResponse = response.json().await.unwrap();
+    let id = response_data.id;
+
+    // Act - Part 1, remove the quote
+    let remove_response = app
+        .application_client
+        .delete(format!("{}/19/remove/{}", &app.application_address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let cite_response = app
+        .application_client
+        .get(format!("{}/19/cite/{}", &app.application_address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let trash_response = app
+        .application_client
+        .get(format!("{}/19/trash", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert - the quote is hidden, but still in the trash
+    assert_eq!(remove_response.status(), StatusCode::OK);
+    assert_eq!(cite_response.status(), StatusCode::NOT_FOUND);
+    let trash_data: Vec<TrashApiResponse> = trash_response.json().await.unwrap();
+    assert_eq!(trash_data.len(), 1);
+    assert_eq!(trash_data[0].id, id);
+    assert!(trash_data[0].deleted_at.parse::<DateTime<Utc>>().is_ok());
+
+    // Act - Part 2, restore the quote
+    let restore_response = app
+        .application_client
+        .post(format!("{}/19/restore/{}", &app.application_address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let cite_response = app
+        .application_client
+        .get(format!("{}/19/cite/{}", &app.application_address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert - the quote is back
+    assert_eq!(restore_response.status(), StatusCode::OK);
+    assert_eq!(cite_response.status(), StatusCode::OK);
+
+    // Act - Part 3, reset the book and empty the trash
+    app.application_client
+        .post(format!("{}/19/reset", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let purge_response = app
+        .application_client
+        .delete(format!("{}/19/purge", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let restore_response = app
+        .application_client
+        .post(format!("{}/19/restore/{}", &app.application_address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert - the quote is gone for good
+    assert_eq!(purge_response.status(), StatusCode::OK);
+    assert_eq!(restore_response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn day19_search_endpoint_returns_ranked_matches_with_snippets_and_200_ok() {
+    // Arrange
+    let app = spawn_app().await;
+    let quotes = [
+        ("Santa", "Ho ho ho! Merry Christmas!"),
+        ("Santa", "Cookies and milk, please."),
+        ("Rudolph", "My nose glows on Christmas Eve."),
+        ("Grinch", "Bah humbug!"),
+    ];
+    for (author, quote) in quotes {
+        app.application_client
+            .post(format!("{}/19/draft", &app.application_address))
+            .json(&json!({"author": author, "quote": quote}))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+    }
+
+    // Act - Part 1, search by words
+    let response = app
+        .application_client
+        .get(format!(
+            "{}/19/search?q=christmas",
+            &app.application_address
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let response_data: SearchApiResponse = response.json().await.unwrap();
+    assert_eq!(response_data.page, 1);
+    assert_eq!(response_data.results.len(), 2);
+    assert!(response_data.next_token.is_none());
+    assert!(response_data
+        .results
+        .iter()
+        .all(|r| r.snippet.contains("<mark>Christmas</mark>")));
+
+    // Act - Part 2, search by words and author
+    let response = app
+        .application_client
+        .get(format!(
+            "{}/19/search?q=christmas&author=rudolph",
+            &app.application_address
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let response_data: SearchApiResponse = response.json().await.unwrap();
+    assert_eq!(response_data.results.len(), 1);
+    assert_eq!(response_data.results[0].author, "Rudolph");
+    assert_eq!(
+        response_data.results[0].quote,
+        "My nose glows on Christmas Eve."
+    );
+
+    // Act - Part 3, a search without any terms is rejected
+    let response = app
+        .application_client
+        .get(format!("{}/19/search", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn day19_export_and_import_endpoints_round_trip_the_quote_book() {
+    // Arrange
+    let app = spawn_app().await;
+    let drafts = [
+        json!({"author": "Santa", "quote": "Ho ho ho!", "tags": ["Santa", "christmas"]}),
+        json!({"author": "Santa", "quote": "Cookies, please."}),
+    ];
+    for draft in drafts {
+        app.application_client
+            .post(format!("{}/19/draft", &app.application_address))
+            .json(&draft)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+    }
+
+    // Act - Part 1, export as JSON Lines and CSV
+    let jsonl_export = app
+        .application_client
+        .get(format!(
+            "{}/19/export?format=jsonl",
+            &app.application_address
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .text()
+        .await
+        .unwrap();
+    let csv_export = app
+        .application_client
+        .get(format!("{}/19/export?format=csv", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .text()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(jsonl_export.lines().count(), 2);
+    let first: ApiResponse = serde_json::from_str(jsonl_export.lines().next().unwrap()).unwrap();
+    assert_eq!(first.quote, "Ho ho ho!");
+    assert_eq!(first.version, 1);
+    let first_line: serde_json::Value =
+        serde_json::from_str(jsonl_export.lines().next().unwrap()).unwrap();
+    assert_eq!(first_line["tags"], json!(["christmas", "santa"]));
+    assert_eq!(csv_export.lines().count(), 3);
+    assert!(csv_export.starts_with("id,author,quote,created_at,version,deleted_at,tags"));
+
+    // Act - Part 2, importing the same ids again is rejected line by line
+    let duplicate_import = app
+        .application_client
+        .post(format!(
+            "{}/19/import?format=jsonl",
+            &app.application_address
+        ))
+        .body(jsonl_export.clone())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(duplicate_import.status(), StatusCode::BAD_REQUEST);
+    let duplicate_data: ImportApiResponse = duplicate_import.json().await.unwrap();
+    assert_eq!(duplicate_data.imported, 0);
+    let lines: Vec<u64> = duplicate_data.errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, vec![1, 2]);
+
+    // Act - Part 3, importing the CSV export with fresh ids succeeds
+    let csv_import = app
+        .application_client
+        .post(format!(
+            "{}/19/import?format=csv&ids=regenerate",
+            &app.application_address
+        ))
+        .body(csv_export)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(csv_import.status(), StatusCode::CREATED);
+    let csv_data: ImportApiResponse = csv_import.json().await.unwrap();
+    assert_eq!(csv_data.imported, 2);
+    assert!(csv_data.errors.is_empty());
+    let tagged: serde_json::Value = app
+        .application_client
+        .get(format!("{}/19/tags/santa", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(tagged["quotes"].as_array().unwrap().len(), 2);
+
+    // Act - Part 4, preserved ids are restored after the book is purged
+    app.application_client
+        .post(format!("{}/19/reset", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    app.application_client
+        .delete(format!("{}/19/purge", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let jsonl_import = app
+        .application_client
+        .post(format!("{}/19/import", &app.application_address))
+        .body(jsonl_export)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let cite_response = app
+        .application_client
+        .get(format!("{}/19/cite/{}", &app.application_address, first.id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(jsonl_import.status(), StatusCode::CREATED);
+    assert_eq!(cite_response.status(), StatusCode::OK);
+    let cited: serde_json::Value = cite_response.json().await.unwrap();
+    assert_eq!(cited["tags"], json!(["christmas", "santa"]));
+}
+
+#[tokio::test]
+async fn day19_cite_endpoint_returns_problem_body_with_404_not_found_for_unknown_id() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .application_client
+        .get(format!(
+            "{}/19/cite/{}",
+            &app.application_address,
+            Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.headers().get("Content-Type").unwrap(),
+        "application/problem+json"
+    );
+    let response_data: ProblemApiResponse = response.json().await.unwrap();
+    assert_eq!(response_data.status, 404);
+    assert_eq!(response_data.title, "Not Found");
+}
+
+#[tokio::test]
+async fn day19_endpoints_work_against_the_in_memory_quote_repository() {
+    // Arrange
+    let app = spawn_app_in_memory().await;
+    let request_body = json!({
+      "author":"Santa",
+      "quote":"Ho ho ho!"
+    });
+
+    // Act
+    let draft_response = app
+        .application_client
+        .post(format!("{}/19/draft", &app.application_address))
+        .json(&request_body)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let draft_data: ApiResponse = draft_response.json().await.unwrap();
+
+    let cite_response = app
+        .application_client
+        .get(format!(
+            "{}/19/cite/{}",
+            &app.application_address, draft_data.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let search_response = app
+        .application_client
+        .get(format!("{}/19/search?q=ho", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(cite_response.status(), StatusCode::OK);
+    let cite_data: ApiResponse = cite_response.json().await.unwrap();
+    assert_eq!(cite_data.id, draft_data.id);
+    assert_eq!(cite_data.quote, "Ho ho ho!");
+
+    assert_eq!(search_response.status(), StatusCode::OK);
+    let search_data: SearchApiResponse = search_response.json().await.unwrap();
+    assert_eq!(search_data.results.len(), 1);
+    assert_eq!(
+        search_data.results[0].snippet,
+        "<mark>Ho</mark> <mark>ho</mark> <mark>ho</mark>!"
+    );
+}
+
+#[tokio::test]
+async fn day19_csv_export_of_an_empty_book_still_has_its_header_row() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let csv_export = app
+        .application_client
+        .get(format!("{}/19/export?format=csv", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .text()
+        .await
+        .unwrap();
+
+    let csv_import = app
+        .application_client
+        .post(format!("{}/19/import?format=csv", &app.application_address))
+        .body(csv_export.clone())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(
+        csv_export,
+        "id,author,quote,created_at,version,deleted_at,tags\n"
+    );
+    assert_eq!(csv_import.status(), StatusCode::CREATED);
+    let csv_data: ImportApiResponse = csv_import.json().await.unwrap();
+    assert_eq!(csv_data.imported, 0);
+    assert!(csv_data.errors.is_empty());
+}
+
+#[tokio::test]
+async fn day19_purge_by_id_leaves_an_active_quote_alone_in_both_repositories() {
+    for app in [spawn_app().await, spawn_app_in_memory().await] {
+        // Arrange
+        let draft_response = app
+            .application_client
+            .post(format!("{}/19/draft", &app.application_address))
+            .json(&json!({"author":"Santa", "quote":"Ho ho ho!", "tags":["christmas"]}))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let draft_data: ApiResponse = draft_response.json().await.unwrap();
+
+        // Act
+        let purge_response = app
+            .application_client
+            .delete(format!(
+                "{}/19/purge/{}",
+                &app.application_address, draft_data.id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        let cite_response = app
+            .application_client
+            .get(format!(
+                "{}/19/cite/{}",
+                &app.application_address, draft_data.id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(purge_response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(cite_response.status(), StatusCode::OK);
+        let cite_data: ApiResponse = cite_response.json().await.unwrap();
+        assert_eq!(cite_data.quote, "Ho ho ho!");
+    }
+}
+
+#[tokio::test]
+async fn day19_tags_endpoints_count_tags_and_list_quotes_under_a_tag_with_200_ok() {
+    // Arrange
+    let app = spawn_app().await;
+    let drafts = [
+        json!({"author":"Santa", "quote":"Ho ho ho!", "tags":["Cheer", "christmas "]}),
+        json!({"author":"Rudolph", "quote":"Shine bright!", "tags":["christmas"]}),
+        json!({"author":"Grinch", "quote":"Bah!"}),
+    ];
+    let mut ids = Vec::new();
+    for draft in drafts {
+        let response = app
+            .application_client
+            .post(format!("{}/19/draft", &app.application_address))
+            .json(&draft)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let response_data: ApiResponse = response.json().await.unwrap();
+        ids.push(response_data.id);
+    }
+
+    // retag the grinch, leaving the quote otherwise unchanged
+    let undo_response = app
+        .application_client
+        .put(format!("{}/19/undo/{}", &app.application_address, ids[2]))
+        .json(&json!({"author":"Grinch", "quote":"Bah!", "tags":["grumpy"]}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Act
+    let tags_response = app
+        .application_client
+        .get(format!("{}/19/tags", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let tag_response = app
+        .application_client
+        .get(format!("{}/19/tags/christmas", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let unknown_tag_response = app
+        .application_client
+        .get(format!("{}/19/tags/easter", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let bad_tag_response = app
+        .application_client
+        .post(format!("{}/19/draft", &app.application_address))
+        .json(&json!({"author":"Santa", "quote":"Ho ho ho!", "tags":[" "]}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(undo_response.status(), StatusCode::OK);
+
+    assert_eq!(tags_response.status(), StatusCode::OK);
+    let tags_data: Vec<TagCountApiResponse> = tags_response.json().await.unwrap();
+    let tags: Vec<(&str, i64)> = tags_data
+        .iter()
+        .map(|t| (t.tag.as_str(), t.count))
+        .collect();
+    assert_eq!(tags, vec![("christmas", 2), ("cheer", 1), ("grumpy", 1)]);
+
+    assert_eq!(tag_response.status(), StatusCode::OK);
+    let tag_data: TagApiResponse = tag_response.json().await.unwrap();
+    assert_eq!(tag_data.tag, "christmas");
+    let authors: Vec<&str> = tag_data.quotes.iter().map(|q| q.author.as_str()).collect();
+    assert_eq!(authors, vec!["Santa", "Rudolph"]);
+
+    assert_eq!(unknown_tag_response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(bad_tag_response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn day19_today_endpoint_returns_the_same_quote_for_every_caller_and_random_returns_any() {
+    // Arrange
+    let app = spawn_app().await;
+    let drafts = [
+        json!({"author":"Santa", "quote":"Ho ho ho!"}),
+        json!({"author":"Rudolph", "quote":"Shine bright!"}),
+        json!({"author":"Grinch", "quote":"Bah!"}),
+    ];
+    let mut ids = Vec::new();
+    for draft in drafts {
+        let response = app
+            .application_client
+            .post(format!("{}/19/draft", &app.application_address))
+            .json(&draft)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let response_data: ApiResponse = response.json().await.unwrap();
+        ids.push(response_data.id);
+    }
+
+    // Act
+    let mut today_responses = Vec::new();
+    for _ in 0..3 {
+        let response = app
+            .application_client
+            .get(format!("{}/19/today", &app.application_address))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        today_responses.push(response);
+    }
+
+    let author_response = app
+        .application_client
+        .get(format!(
+            "{}/19/today?author=rudolph",
+            &app.application_address
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let random_response = app
+        .application_client
+        .get(format!("{}/19/random", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let unknown_author_response = app
+        .application_client
+        .get(format!(
+            "{}/19/random?author=Easter%20Bunny",
+            &app.application_address
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let mut today_ids = Vec::new();
+    for response in today_responses {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response
+            .headers()
+            .get("Cache-Control")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("public, max-age="));
+        let response_data: ApiResponse = response.json().await.unwrap();
+        today_ids.push(response_data.id);
+    }
+    assert!(ids.contains(&today_ids[0]));
+    assert!(today_ids.iter().all(|id| *id == today_ids[0]));
+
+    assert_eq!(author_response.status(), StatusCode::OK);
+    let author_data: ApiResponse = author_response.json().await.unwrap();
+    assert_eq!(author_data.author, "Rudolph");
+
+    assert_eq!(random_response.status(), StatusCode::OK);
+    let random_data: ApiResponse = random_response.json().await.unwrap();
+    assert!(ids.contains(&random_data.id));
+
+    assert_eq!(unknown_author_response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn day19_export_endpoint_returns_429_too_many_requests_once_its_budget_is_spent() {
+    // Arrange
+    let app = spawn_app_in_memory().await;
+    for _ in 0..5 {
+        let response = app
+            .application_client
+            .get(format!("{}/19/export", &app.application_address))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(response.status().is_success());
+    }
+
+    // Act
+    let limited_response = app
+        .application_client
+        .get(format!("{}/19/export", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let list_response = app
+        .application_client
+        .get(format!("{}/19/list", &app.application_address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(limited_response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(limited_response.headers()["retry-after"], "2");
+    assert_eq!(
+        limited_response.headers()["content-type"],
+        "application/problem+json"
+    );
+
+    assert!(list_response.status().is_success());
+}
@@ -1,8 +1,9 @@
 // tests/api/day9.rs
 
 // dependences
-use crate::helpers::spawn_app;
+use crate::helpers::{spawn_app, spawn_apps_sharing_milk_buckets, ADMIN_TOKEN};
 use axum::http::StatusCode;
+use serde_json::{json, Value};
 
 #[tokio::test]
 async fn day_9_task1_responds_with_200_ok_if_milk_is_available() {
@@ -26,9 +27,14 @@ async fn day_9_task1_returns_429_too_many_requests_if_bucket_empty() {
     // Arrange
     let app = spawn_app().await;
 
-    {
-        let rate_limiter = app.application_state.rate_limiter.read().await;
-        while rate_limiter.try_acquire(1) {}
+    for _ in 0..5 {
+        let response = app
+            .application_client
+            .post(format!("{}/9/milk", &app.application_address))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(response.status().is_success());
     }
 
     // Act
@@ -41,6 +47,9 @@ async fn day_9_task1_returns_429_too_many_requests_if_bucket_empty() {
 
     // Assert
     assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["x-ratelimit-limit"], "5");
+    assert_eq!(response.headers()["x-ratelimit-remaining"], "0");
+    assert_eq!(response.headers()["retry-after"], "1");
     let response_body = response
         .text()
         .await
@@ -48,3 +57,346 @@ async fn day_9_task1_returns_429_too_many_requests_if_bucket_empty() {
     let expected_body = "No milk available\n";
     assert_eq!(response_body, expected_body);
 }
+
+#[tokio::test]
+async fn day_9_task1_gives_each_client_its_own_bucket() {
+    // Arrange
+    let app = spawn_app().await;
+    let milk = |api_key: &'static str| {
+        app.application_client
+            .post(format!("{}/9/milk", &app.application_address))
+            .header("X-Api-Key", api_key)
+            .send()
+    };
+    for _ in 0..5 {
+        let response = milk("grinch").await.expect("Failed to execute request.");
+        assert!(response.status().is_success());
+    }
+
+    // Act
+    let drained_response = milk("grinch").await.expect("Failed to execute request.");
+    let other_response = milk("santa").await.expect("Failed to execute request.");
+
+    let refill_response = app
+        .application_client
+        .post(format!("{}/9/refill", &app.application_address))
+        .header("X-Api-Key", "grinch")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let refilled_response = milk("grinch").await.expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(drained_response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    assert!(other_response.status().is_success());
+    assert_eq!(other_response.headers()["x-ratelimit-remaining"], "4");
+    assert_eq!(other_response.headers()["x-ratelimit-reset"], "1");
+    assert!(other_response.headers().get("retry-after").is_none());
+
+    assert!(refill_response.status().is_success());
+    assert!(refilled_response.status().is_success());
+}
+
+#[tokio::test]
+async fn day_9_task1_keys_unknown_api_keys_and_spoofed_addresses_by_the_proxy_address() {
+    // Arrange
+    let app = spawn_app().await;
+    let milk = |api_key: String, forwarded_for: &'static str| {
+        app.application_client
+            .post(format!("{}/9/milk", &app.application_address))
+            .header("X-Api-Key", api_key)
+            .header("X-Forwarded-For", forwarded_for)
+            .send()
+    };
+    for n in 0..5 {
+        let response = milk(format!("made-up-{}", n), "203.0.113.7")
+            .await
+            .expect("Failed to execute request.");
+        assert!(response.status().is_success());
+    }
+
+    // Act
+    let unknown_key_response = milk("made-up-5".to_string(), "203.0.113.7")
+        .await
+        .expect("Failed to execute request.");
+    let spoofed_response = milk("made-up-6".to_string(), "198.51.100.1, 203.0.113.7")
+        .await
+        .expect("Failed to execute request.");
+    let other_address_response = milk("made-up-7".to_string(), "198.51.100.1")
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(unknown_key_response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(spoofed_response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(other_address_response.status().is_success());
+}
+
+#[tokio::test]
+async fn day_9_config_returns_403_forbidden_without_the_admin_token() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .application_client
+        .put(format!("{}/9/config", &app.application_address))
+        .bearer_auth("not-the-admin-token")
+        .json(&json!({"capacity": 10}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/problem+json"
+    );
+}
+
+#[tokio::test]
+async fn day_9_config_returns_400_bad_request_for_an_out_of_range_capacity() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .application_client
+        .put(format!("{}/9/config", &app.application_address))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"capacity": 0, "refill_interval_ms": 500}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response_body: Value = response
+        .json()
+        .await
+        .expect("Unable to retrieve response body.");
+    assert_eq!(
+        response_body["detail"],
+        "The capacity must be between 1 and 1000."
+    );
+}
+
+#[tokio::test]
+async fn day_9_config_changes_the_bucket_reported_by_status_and_used_by_refill() {
+    // Arrange
+    let app = spawn_app().await;
+    let status = || {
+        app.application_client
+            .get(format!("{}/9/status", &app.application_address))
+            .header("X-Api-Key", "santa")
+            .send()
+    };
+    let milk = || {
+        app.application_client
+            .post(format!("{}/9/milk", &app.application_address))
+            .header("X-Api-Key", "santa")
+            .send()
+    };
+    milk().await.expect("Failed to execute request.");
+    milk().await.expect("Failed to execute request.");
+
+    // Act
+    let drained_status: Value = status()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Unable to retrieve response body.");
+
+    let config_response = app
+        .application_client
+        .put(format!("{}/9/config", &app.application_address))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"capacity": 2, "refill_interval_ms": 60000}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let config_body: Value = config_response
+        .json()
+        .await
+        .expect("Unable to retrieve response body.");
+    let resized_status: Value = status()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Unable to retrieve response body.");
+
+    app.application_client
+        .post(format!("{}/9/refill", &app.application_address))
+        .header("X-Api-Key", "santa")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let refilled_response = milk().await.expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(drained_status["capacity"], 5);
+    assert_eq!(drained_status["refill_interval_ms"], 1000);
+    assert_eq!(drained_status["remaining"], 3);
+
+    assert_eq!(
+        config_body,
+        json!({"capacity": 2, "refill_interval_ms": 60000})
+    );
+    assert_eq!(resized_status["capacity"], 2);
+    assert_eq!(resized_status["remaining"], 2);
+
+    assert!(refilled_response.status().is_success());
+    assert_eq!(refilled_response.headers()["x-ratelimit-limit"], "2");
+    assert_eq!(refilled_response.headers()["x-ratelimit-remaining"], "1");
+}
+
+#[tokio::test]
+async fn day_9_task1_still_converts_the_single_unit_payloads() {
+    // Arrange
+    let app = spawn_app().await;
+    let milk = |request_body: Value| {
+        app.application_client
+            .post(format!("{}/9/milk", &app.application_address))
+            .json(&request_body)
+            .send()
+    };
+
+    // Act
+    let response = milk(json!({"liters": 5}))
+        .await
+        .expect("Failed to execute request.");
+    let decimal_response = milk(json!({"gallons": 0.1}))
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(response.status().is_success());
+    let response_body: Value = response
+        .json()
+        .await
+        .expect("Unable to retrieve response body.");
+    let gallons = response_body["gallons"].as_f64().unwrap();
+    assert!((gallons - 1.3208602).abs() < 1e-6);
+
+    // the quantity is read as it was written, without passing through a single precision float
+    assert!(decimal_response.status().is_success());
+    let response_body: Value = decimal_response
+        .json()
+        .await
+        .expect("Unable to retrieve response body.");
+    assert_eq!(response_body["liters"].as_f64().unwrap(), 0.378_541_178_4);
+}
+
+#[tokio::test]
+async fn day_9_task1_converts_between_any_two_units_with_the_requested_precision() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .application_client
+        .post(format!("{}/9/milk", &app.application_address))
+        .json(&json!({"from": "barrels", "to": "cups", "value": 0.5, "precision": 2}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(response.status().is_success());
+    let response_body: Value = response
+        .json()
+        .await
+        .expect("Unable to retrieve response body.");
+    assert_eq!(response_body, json!({"unit": "cups", "value": 336.0}));
+}
+
+#[tokio::test]
+async fn day_9_task1_returns_400_bad_request_for_an_unknown_unit() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .application_client
+        .post(format!("{}/9/milk", &app.application_address))
+        .json(&json!({"from": "liters", "to": "hogsheads", "value": 1}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response_body: Value = response
+        .json()
+        .await
+        .expect("Unable to retrieve response body.");
+    assert_eq!(response_body["detail"], "Unknown unit of volume: hogsheads");
+}
+
+#[tokio::test]
+async fn day_9_shared_milk_buckets_hold_across_instances() {
+    // Arrange
+    let (first_app, second_app) = spawn_apps_sharing_milk_buckets().await;
+    let milk = |app: &'static str| {
+        let app = if app == "first" {
+            &first_app
+        } else {
+            &second_app
+        };
+        app.application_client
+            .post(format!("{}/9/milk", &app.application_address))
+            .header("X-Api-Key", "santa")
+            .send()
+    };
+    for app in ["first", "second", "first", "second", "first"] {
+        let response = milk(app).await.expect("Failed to execute request.");
+        assert!(response.status().is_success());
+    }
+
+    // Act
+    let drained_response = milk("second").await.expect("Failed to execute request.");
+
+    let config_response = first_app
+        .application_client
+        .put(format!("{}/9/config", &first_app.application_address))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"capacity": 8}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let status: Value = second_app
+        .application_client
+        .get(format!("{}/9/status", &second_app.application_address))
+        .header("X-Api-Key", "santa")
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Unable to retrieve response body.");
+
+    second_app
+        .application_client
+        .post(format!("{}/9/refill", &second_app.application_address))
+        .header("X-Api-Key", "santa")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let refilled_response = milk("first").await.expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(drained_response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(drained_response.headers()["x-ratelimit-remaining"], "0");
+
+    assert!(config_response.status().is_success());
+    assert_eq!(status["capacity"], 8);
+    assert_eq!(status["refill_interval_ms"], 1000);
+
+    assert!(refilled_response.status().is_success());
+    assert_eq!(refilled_response.headers()["x-ratelimit-limit"], "8");
+    assert_eq!(refilled_response.headers()["x-ratelimit-remaining"], "7");
+}
@@ -3,12 +3,16 @@
 // dependencies
 use reqwest::Client;
 use shuttlings_cch24::telemetry::{get_subscriber, init_subscriber};
-use shuttlings_cch24::{AppState, Application};
+use shuttlings_cch24::{
+    AppState, Application, BucketConfig, InMemoryGameRepository, InMemoryQuoteRepository,
+    PostgresBucketRepository,
+};
 use sqlx::{postgres::PgConnectOptions, Connection, Executor, PgConnection, PgPool};
 use std::env::var;
 use std::io::{sink, stdout};
 use std::net::TcpListener;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 use testcontainers_modules::{postgres, testcontainers::runners::AsyncRunner};
 use uuid::Uuid;
 
@@ -97,6 +101,16 @@ pub struct TestApp {
     pub application_state: AppState,
 }
 
+// static constants for the Day 9 milk bucket used by every test application
+const MILK_CAPACITY: usize = 5;
+const MILK_REFILL_RATE: u64 = 1;
+
+// static constant for the bearer token accepted by the admin endpoints of every test application
+pub const ADMIN_TOKEN: &str = "test-admin-token";
+
+// static constant for the API keys which give a client its own milk bucket in every test application
+pub const API_KEYS: [&str; 2] = ["grinch", "santa"];
+
 pub async fn spawn_app() -> TestApp {
     // setup tracing
     LazyLock::force(&TRACING);
@@ -106,9 +120,52 @@ pub async fn spawn_app() -> TestApp {
     let pool = configure_database(&db_config).await;
 
     // build the app for testing
-    let milk_capacity = 5;
-    let milk_refill_rate = 1;
-    let app_state = AppState::new(milk_capacity, milk_refill_rate, pool);
+    let app_state = AppState::new(MILK_CAPACITY, MILK_REFILL_RATE, pool);
+    spawn_app_with_state(app_state).await
+}
+
+// function to spawn a test application which keeps its quotes and games in memory, without a database
+pub async fn spawn_app_in_memory() -> TestApp {
+    // setup tracing
+    LazyLock::force(&TRACING);
+
+    // build the app for testing
+    let app_state = AppState::with_repositories(
+        MILK_CAPACITY,
+        MILK_REFILL_RATE,
+        Arc::new(InMemoryQuoteRepository::default()),
+        Arc::new(InMemoryGameRepository::default()),
+    );
+    spawn_app_with_state(app_state).await
+}
+
+// function to spawn two test applications, standing in for two instances of the app, which share one
+// database and keep their Day 9 milk buckets in it
+pub async fn spawn_apps_sharing_milk_buckets() -> (TestApp, TestApp) {
+    // setup tracing
+    LazyLock::force(&TRACING);
+
+    // setup the database
+    let db_config = DatabaseSettings::new();
+    let pool = configure_database(&db_config).await;
+
+    // build the apps for testing
+    let milk_bucket_config =
+        BucketConfig::new(MILK_CAPACITY, Duration::from_secs(MILK_REFILL_RATE)).unwrap();
+    let build_state = || {
+        AppState::new(MILK_CAPACITY, MILK_REFILL_RATE, pool.clone()).with_milk_buckets(Arc::new(
+            PostgresBucketRepository::new(pool.clone(), milk_bucket_config),
+        ))
+    };
+    let first_app = spawn_app_with_state(build_state()).await;
+    let second_app = spawn_app_with_state(build_state()).await;
+    (first_app, second_app)
+}
+
+async fn spawn_app_with_state(app_state: AppState) -> TestApp {
+    let app_state = app_state
+        .with_admin_token(Some(ADMIN_TOKEN.to_string()))
+        .with_api_keys(API_KEYS.map(str::to_string));
     let application = Application::build(app_state.clone());
     let listener = TcpListener::bind("localhost:0").expect("Failed to bind port.");
     let addr = listener.local_addr().unwrap();